@@ -1,196 +1,540 @@
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use futures::{future, stream::TryStreamExt};
-use itertools::Itertools;
-use octocrab::Octocrab;
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt, TryStreamExt},
+};
+use indicatif::{ProgressBar, ProgressStyle};
+use octocrab::{
+    models::{pulls::PullRequest, Rate, RateLimit, User, UserId},
+    params::State,
+    Octocrab, Page,
+};
 use octocrabby::{
-    block_user, check_follow, cli, get_blocks, models::UserInfo, parse_repo_path, pull_requests,
-    BlockStatus, Exclusions,
+    block::read_protected_logins,
+    block_from_reader, block_usernames, check_follow, cli,
+    models::{ExtendedUser, UserInfo},
+    output::{self, OutputRecord, RecordSink},
+    parse_pull_request_path, parse_repo_path, read_delimited_usernames, scopes, BlockOptions,
+    Config, Exclusions, InputFormat, OctocrabbyClient, RetryConfig,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
 use std::default::Default;
 use std::fs::File;
+use std::io::{IsTerminal, Write};
 use std::time::Duration;
 
 type Void = Result<(), Box<dyn std::error::Error>>;
 
-const GRAPHQL_CHUNK_SIZE: usize = 512;
-const GRAPHQL_RETRIES: u32 = 4;
-const GRAPHQL_DELAY: Duration = Duration::from_secs(5);
+const GRAPHQL_LARGE_CHUNK_SIZE: usize = 500;
+const BLOCK_FAILURE_EXIT_CODE: i32 = 2;
 
 #[tokio::main]
 async fn main() -> Void {
     let opts: Opts = Opts::parse();
-    let _ = cli::init_logging(opts.verbose);
-    let instance = octocrabby::init(opts.token)?;
+    let _ = cli::init_logging(opts.verbose, opts.quiet);
+    let show_progress = opts.progress && !opts.quiet;
+    let config = Config {
+        retry: RetryConfig {
+            retries: opts.retries,
+            base_delay: Duration::from_millis(opts.retry_base_delay_ms),
+            timeout: None,
+        },
+        prefetch_pages: opts.prefetch_pages,
+        concurrency: opts.jobs.into(),
+        continue_on_error: opts.continue_on_error,
+        rest_timeout: opts.rest_timeout_ms.map(Duration::from_millis),
+        graphql_timeout: opts.graphql_timeout_ms.map(Duration::from_millis),
+        base_url: opts.base_url,
+        ..Config::default()
+    };
+    let client = OctocrabbyClient::new(opts.token, config)?;
+    let instance = client.instance();
+    let delimiter = opts.csv_delimiter;
+    let json_result = opts.command.uses_json_result(opts.output_format)?;
+    let output_format = opts
+        .output_format
+        .map(output::OutputFormat::from)
+        .unwrap_or_default();
 
     match opts.command {
-        Command::BlockUsers { org, force } => {
-            // Note that only the first field is used, and is expected to be a GitHub login username
-            let mut reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .from_reader(std::io::stdin());
-            let mut usernames = vec![];
+        Command::BlockUsers {
+            force,
+            input_format,
+            block,
+        } => {
+            check_org(&client, block.org.as_deref(), block.skip_org_check).await?;
 
-            for record in reader.records() {
-                usernames.push(record?.get(0).unwrap().to_string());
+            if force {
+                log::warn!("--force is deprecated; use --skip-known-check instead");
             }
 
-            if !force {
-                let known: HashSet<String> = octocrabby::get_blocks(&instance, org.as_deref())
-                    .and_then(|user| future::ok(user.login))
-                    .try_collect()
-                    .await?;
+            let mut options = block.to_block_options(client.rest_retry(), delimiter)?;
+            options.skip_known_check |= force;
 
-                let unfiltered_size = usernames.len();
+            let summary = block_from_reader(
+                instance,
+                block.org.as_deref(),
+                std::io::stdin(),
+                input_format.into(),
+                &options,
+            )
+            .await?;
 
-                usernames.retain(|username| !known.contains(username));
+            if block.dry_run {
+                let mut writer = csv_writer(delimiter);
+                for login in &summary.blocked {
+                    writer.write_record([login])?;
+                }
+                writer.flush()?;
 
-                log::warn!(
-                    "Skipping {} known blocked users",
-                    unfiltered_size - usernames.len()
-                );
+                log::info!("Would block {} users", summary.blocked.len());
+            } else {
+                log::info!("Newly blocked {} users", summary.newly_blocked);
             }
 
-            for username in usernames {
-                match block_user(&instance, org.as_deref(), &username).await? {
-                    BlockStatus::NewlyBlocked => log::info!("Successfully blocked {}", username),
-                    BlockStatus::AlreadyBlocked => log::warn!("{} was already blocked", username),
-                    BlockStatus::UserNotFound => log::warn!("{} was not found", username),
-                    BlockStatus::OtherSuccess(status_code) => {
-                        log::error!("Unknown success status code: {:?}", status_code)
-                    }
-                    BlockStatus::OtherNonSuccess(message) => {
-                        log::error!("Unknown non-success message: {}", message)
-                    }
-                };
+            if summary.has_failures(block.strict) {
+                log::error!("Some blocks failed: {:?}", summary);
+                std::process::exit(BLOCK_FAILURE_EXIT_CODE);
             }
         }
-        Command::ListFollowers => {
-            octocrabby::get_followers(&instance)
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+        Command::BlockFollowersOf { user, yes, block } => {
+            check_org(&client, block.org.as_deref(), block.skip_org_check).await?;
+
+            let options = block.to_block_options(client.rest_retry(), delimiter)?;
+            let reason = format!("Follower of {}", user);
+            let usernames = client
+                .followers_of(&user)
+                .map_ok(|follower| (follower.login, Some(reason.clone())))
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            log::info!("Found {} followers of {}", usernames.len(), user);
+
+            if !block.dry_run
+                && !yes
+                && !confirm(&format!(
+                    "Block up to {} followers of {}{}?",
+                    usernames.len(),
+                    user,
+                    block
+                        .org
+                        .as_ref()
+                        .map(|org| format!(" from {}", org))
+                        .unwrap_or_default()
+                ))?
+            {
+                log::warn!("Not blocking any followers of {}", user);
+                return Ok(());
+            }
+
+            let summary =
+                block_usernames(instance, block.org.as_deref(), usernames, &options).await?;
+
+            let mut writer = csv_writer(delimiter);
+            for login in &summary.blocked {
+                writer.write_record([login])?;
+            }
+            writer.flush()?;
+
+            log::info!(
+                "{} {} followers of {} (skipped {} protected, {} followed, and {} known blocked)",
+                if block.dry_run {
+                    "Would block"
+                } else {
+                    "Newly blocked"
+                },
+                summary.blocked.len(),
+                user,
+                summary.protected,
+                summary.following,
+                summary.known_blocked
+            );
+
+            if summary.has_failures(block.strict) {
+                log::error!("Some blocks failed: {:?}", summary);
+                std::process::exit(BLOCK_FAILURE_EXIT_CODE);
+            }
         }
-        Command::ListFollowing => {
-            octocrabby::get_following(&instance)
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+        Command::ListFollowers {
+            since,
+            resume_from,
+            with_age,
+            min_followers,
+            max_followers,
+            dedupe_output,
+        } => match since {
+            Some(since_file) => {
+                let seen = load_seen_ids(&since_file)?;
+                let mut current = HashSet::new();
+                let mut count = 0;
+                let mut duplicates = 0;
+                let mut sink = output_format.sink(std::io::stdout(), delimiter);
+
+                let result = client
+                    .followers()
+                    .err_into::<Box<dyn std::error::Error>>()
+                    .try_for_each(|user| {
+                        let is_new = current.insert(user.id.into_inner());
+
+                        if dedupe_output && !is_new {
+                            duplicates += 1;
+                        } else if !seen.contains(&user.id.into_inner()) {
+                            count += 1;
+                            return future::ready(
+                                sink.write_record(&OutputRecord::new(&user))
+                                    .map_err(Into::into),
+                            );
+                        }
+                        future::ok(())
+                    })
+                    .await;
+                // Finish even after an error, since some formats buffer the records already seen
+                sink.finish()?;
+                result?;
+
+                report_duplicates(dedupe_output, duplicates);
+                save_seen_ids(&since_file, &current)?;
+                report_count(opts.quiet, count, "new followers");
+            }
+            None => {
+                let count = print_user_pages(
+                    &client,
+                    client.followers_pages(resume_from.as_deref()),
+                    UserListOptions {
+                        with_age,
+                        min_followers,
+                        max_followers,
+                        dedupe_output,
+                        progress: show_progress,
+                        ..UserListOptions::default()
+                    },
+                    output_format.sink(std::io::stdout(), delimiter),
+                )
+                .await?;
+                report_count(opts.quiet, count, "followers");
+            }
+        },
+        Command::ListFollowing {
+            resume_from,
+            dedupe_output,
+        } => {
+            let count = print_user_pages(
+                &client,
+                client.following_pages(resume_from.as_deref()),
+                UserListOptions {
+                    dedupe_output,
+                    progress: show_progress,
+                    ..UserListOptions::default()
+                },
+                output_format.sink(std::io::stdout(), delimiter),
+            )
+            .await?;
+            report_count(opts.quiet, count, "following");
         }
-        Command::ListBlocks { org } => {
-            get_blocks(&instance, org.as_deref())
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+        Command::ListBlocks {
+            org,
+            resume_from,
+            with_age,
+            with_created_at,
+            skip_org_check,
+            dedupe_output,
+        } => {
+            check_org(&client, org.as_deref(), skip_org_check).await?;
+
+            let count = print_user_pages(
+                &client,
+                client.blocks_pages(org.as_deref(), resume_from.as_deref()),
+                UserListOptions {
+                    with_age,
+                    with_created_at,
+                    dedupe_output,
+                    progress: show_progress,
+                    ..UserListOptions::default()
+                },
+                output_format.sink(std::io::stdout(), delimiter),
+            )
+            .await?;
+            report_count(opts.quiet, count, "blocked");
         }
         Command::ListPrContributors {
             repo_path,
-            omit_twitter,
+            repos_file,
+            base,
+            report,
+        } => {
+            let mut repo_paths = vec![];
+
+            if let Some(repo_path) = repo_path {
+                repo_paths.push(repo_path);
+            }
+
+            if let Some(repos_file) = repos_file {
+                repo_paths.extend(read_repo_paths(&repos_file)?);
+            }
+
+            let exclusions = report.load_exclusions(delimiter)?;
+            let mut counts = ContributorCounts::new(report.group_by_id);
+            let mut progress = Progress::new(show_progress);
+
+            for repo_path in repo_paths {
+                if let Some((owner, repo)) = parse_repo_path(&repo_path) {
+                    log::info!("Loading pull requests for {}", repo_path);
+                    count_contributor_prs(
+                        &client,
+                        owner,
+                        repo,
+                        report.pr_state(),
+                        base.as_deref(),
+                        &exclusions,
+                        &mut counts,
+                        &mut progress,
+                    )
+                    .await?;
+                } else {
+                    log::error!("Invalid repository path: {}", repo_path);
+                }
+            }
+
+            progress.finish();
+
+            write_contributor_report(&client, counts, &report, delimiter).await?;
+        }
+        Command::ListOrgContributors {
+            org,
+            exclude_archived,
+            report,
+        } => {
+            let exclusions = report.load_exclusions(delimiter)?;
+
+            log::info!("Loading repositories for {}", org);
+            let repos = client
+                .org_repos(&org)
+                .try_filter(|repo| {
+                    future::ready(!(exclude_archived && repo.archived == Some(true)))
+                })
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let mut counts = ContributorCounts::new(report.group_by_id);
+
+            let mut progress = Progress::new(show_progress);
+
+            for repo in repos {
+                log::info!("Loading pull requests for {}/{}", org, repo.name);
+                count_contributor_prs(
+                    &client,
+                    &org,
+                    &repo.name,
+                    report.pr_state(),
+                    None,
+                    &exclusions,
+                    &mut counts,
+                    &mut progress,
+                )
+                .await?;
+            }
+
+            progress.finish();
+
+            write_contributor_report(&client, counts, &report, delimiter).await?;
+        }
+        Command::PruneExclusions {
+            repo_path,
             exclusions_file,
-            ignore_exclusions,
+            write,
+        } => {
+            let (owner, repo) = parse_repo_path(&repo_path)
+                .ok_or_else(|| format!("Invalid repository path: {}", repo_path))?;
+            let mut exclusions =
+                Exclusions::load_with_delimiter(File::open(&exclusions_file)?, delimiter)?;
+
+            let contributors: HashSet<String> = client
+                .pull_requests(owner, repo, State::All)
+                .try_filter_map(|pr| future::ok(pr.user.map(|user| user.login.to_lowercase())))
+                .try_collect()
+                .await?;
+
+            let stale = exclusions
+                .usernames(&repo_path)
+                .into_iter()
+                .filter(|username| !contributors.contains(*username))
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+
+            let mut writer = csv_writer(delimiter);
+
+            for username in &stale {
+                writer.write_record([repo_path.as_str(), username.as_str()])?;
+            }
+
+            writer.flush()?;
+
+            if write && !stale.is_empty() {
+                for username in &stale {
+                    exclusions.remove(&repo_path, username);
+                }
+
+                // Write to a temporary file first so that a failure doesn't lose the exclusions
+                let temp_path = format!("{}.tmp", exclusions_file);
+                exclusions.save_with_delimiter(File::create(&temp_path)?, delimiter)?;
+                std::fs::rename(temp_path, &exclusions_file)?;
+            }
+
+            report_count(opts.quiet, stale.len(), "stale exclusions");
+        }
+        Command::UserContributions { user, org } => {
+            let mut writer = csv_writer(delimiter);
+
+            for (repo, count) in count_user_prs_by_repo(&client, &org, &user).await? {
+                writer.write_record(&[format!("{}/{}", org, repo), count.to_string()])?;
+            }
+        }
+        Command::ListStargazers {
+            repo_path,
+            starred_at,
         } => {
             if let Some((owner, repo)) = parse_repo_path(&repo_path) {
-                let exclusions = if ignore_exclusions {
-                    Exclusions::default()
+                let mut count = 0;
+                let mut sink = output_format.sink(std::io::stdout(), delimiter);
+
+                let result = if starred_at {
+                    client
+                        .stargazers_with_timestamps(owner, repo)
+                        .err_into::<Box<dyn std::error::Error>>()
+                        .try_for_each(|stargazer| {
+                            if let Some(user) = stargazer.user {
+                                let record = OutputRecord {
+                                    starred_at: stargazer.starred_at,
+                                    ..OutputRecord::new(&user)
+                                };
+                                count += 1;
+                                return future::ready(
+                                    sink.write_record(&record).map_err(Into::into),
+                                );
+                            }
+                            future::ok(())
+                        })
+                        .await
                 } else {
-                    let file = File::open(exclusions_file)?;
-                    Exclusions::load(file)?
+                    client
+                        .stargazers(owner, repo)
+                        .err_into::<Box<dyn std::error::Error>>()
+                        .try_for_each(|user| {
+                            count += 1;
+                            future::ready(
+                                sink.write_record(&OutputRecord::new(&user))
+                                    .map_err(Into::into),
+                            )
+                        })
+                        .await
                 };
 
-                log::info!("Loading pull requests");
-                let mut prs = pull_requests(&instance, owner, repo)
-                    .try_collect::<Vec<_>>()
-                    .await?;
-                prs.sort_unstable_by(|pr1, pr2| {
-                    pr1.user
-                        .as_ref()
-                        .map(|user| &user.login)
-                        .cmp(&pr2.user.as_ref().map(|user| &user.login))
-                });
+                sink.finish()?;
+                result?;
 
-                let by_username = prs.into_iter().group_by(|pr| {
-                    (
-                        pr.user.as_ref().map(|user| user.login.clone()),
-                        pr.user.as_ref().map(|user| user.id),
+                report_count(opts.quiet, count, "stargazers");
+            } else {
+                log::error!("Invalid repository path: {}", repo_path);
+            }
+        }
+        Command::DumpPullRequests { repo_path, state } => {
+            let (owner, repo) = parse_repo_path(&repo_path)
+                .ok_or_else(|| format!("Invalid repository path: {}", repo_path))?;
+            let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+            let mut count = 0;
+
+            client
+                .pull_requests(owner, repo, state.into())
+                .try_filter(|pr| future::ready(state.includes(pr)))
+                .err_into::<Box<dyn std::error::Error>>()
+                .try_for_each(|pr| {
+                    count += 1;
+                    future::ready(
+                        serde_json::to_writer(&mut output, &pr)
+                            .map_err(Into::into)
+                            .and_then(|_| writeln!(output).map_err(Into::into)),
                     )
-                });
+                })
+                .await?;
 
-                let results = by_username
-                    .into_iter()
-                    .filter_map(|((username, user_id), prs)| {
-                        let batch = prs.collect::<Vec<_>>();
-                        let batch_len = batch.len();
+            output.flush()?;
+            report_count(opts.quiet, count, "pull requests");
+        }
+        Command::PendingFollowBacks { with_age } => {
+            let pending = if with_age {
+                load_pending_follow_backs_with_age(&client).await?
+            } else {
+                load_pending_follow_backs(&client)
+                    .await?
+                    .iter()
+                    .map(OutputRecord::new)
+                    .collect()
+            };
 
-                        let username = username?;
-                        let user_id = user_id?;
-                        let first_pr_date =
-                            batch.into_iter().map(|pr| pr.created_at).min().unwrap()?;
+            let mut sink = output_format.sink(std::io::stdout(), delimiter);
 
-                        Some((username, user_id, batch_len, first_pr_date))
-                    })
-                    .collect::<Vec<_>>();
+            for record in &pending {
+                sink.write_record(record)?;
+            }
 
-                let usernames = results
-                    .iter()
-                    .map(|(username, _, _, _)| username.as_str())
-                    .collect::<Vec<_>>();
-
-                // Load additional information that's only available if you're authenticated
-                let mut additional_info: Option<AdditionalUserInfo> =
-                    if instance.current().user().await.is_ok() {
-                        Some(load_additional_user_info(&instance, &usernames).await?)
-                    } else {
-                        None
-                    };
-
-                let mut writer = csv::Writer::from_writer(std::io::stdout());
-
-                for (username, user_id, pr_count, first_pr_date) in results {
-                    if exclusions.is_excluded(&repo_path, &username) {
-                        log::warn!("Excluded user {}", username);
-                    } else {
-                        let mut record =
-                            vec![username.clone(), user_id.to_string(), pr_count.to_string()];
-
-                        // Add other fields to the record if you're authenticated
-                        if let Some(AdditionalUserInfo {
-                            ref follows_you,
-                            ref you_follow,
-                            ref mut user_info,
-                        }) = additional_info
-                        {
-                            let (age, name, twitter_username) = match user_info.remove(&username) {
-                                Some(info) => (
-                                    (first_pr_date - info.created_at).num_days(),
-                                    info.name.unwrap_or_default(),
-                                    info.twitter_username.unwrap_or_default(),
-                                ),
-                                None => {
-                                    // These values will be used for accounts such as dependabot
-                                    (-1, "".to_string(), "".to_string())
-                                }
-                            };
-
-                            record.push(age.to_string());
-                            record.push(name);
-                            if !omit_twitter {
-                                record.push(twitter_username);
-                            }
-                            record.push(you_follow.contains(&username).to_string());
-                            record.push(follows_you.contains(&username).to_string());
-                        }
+            sink.finish()?;
 
-                        writer.write_record(&record)?;
-                    }
+            report_count(opts.quiet, pending.len(), "followers not followed back");
+        }
+        Command::PrAuthor { pr_path, number } => {
+            let (owner, repo, number) = match number {
+                Some(number) => {
+                    parse_repo_path(&pr_path).map(|(owner, repo)| (owner, repo, number))
                 }
-            } else {
-                log::error!("Invalid repository path: {}", repo_path);
+                None => parse_pull_request_path(&pr_path),
+            }
+            .ok_or_else(|| format!("Invalid pull request path: {}", pr_path))?;
+
+            let pr = match instance.pulls(owner, repo).get(number).await {
+                Err(octocrab::Error::GitHub { source, .. }) if source.message == "Not Found" => {
+                    return Err(
+                        format!("Pull request {}/{}#{} not found", owner, repo, number).into(),
+                    );
+                }
+                other => other?,
+            };
+
+            let user = pr.user.ok_or("Pull request has no author")?;
+            let mut record = vec![user.login.clone(), user.id.to_string()];
+
+            // Add other fields to the record if you're authenticated
+            if instance.current().user().await.is_ok() {
+                let info = octocrabby::get_users_info(instance, &[&user.login])
+                    .await?
+                    .pop();
+                let age = match (info, pr.created_at) {
+                    (Some(info), Some(created_at)) => (created_at - info.created_at).num_days(),
+                    _ => -1,
+                };
+                let (follows_you, you_follow) = load_follow_sets(&client).await?;
+
+                record.push(age.to_string());
+                record.push(you_follow.contains(&user.login).to_string());
+                record.push(follows_you.contains(&user.login).to_string());
+            }
+
+            csv_writer(delimiter).write_record(&record)?;
+        }
+        Command::FollowStatus => {
+            let usernames = read_delimited_usernames(std::io::stdin(), delimiter)?
+                .into_iter()
+                .map(|(username, _)| username)
+                .collect::<Vec<_>>();
+
+            let (follows_you, you_follow) = load_follow_sets(&client).await?;
+            let mut writer = csv_writer(delimiter);
+
+            for username in usernames {
+                let follows_you = follows_you.contains(&username).to_string();
+                let you_follow = you_follow.contains(&username).to_string();
+
+                writer.write_record(&[username, follows_you, you_follow])?;
             }
         }
         Command::CheckFollow { user, follower } => {
@@ -199,10 +543,132 @@ async fn main() -> Void {
                 None => instance.current().user().await?.login,
             };
 
-            let result = check_follow(&instance, &follower, &target_user).await?;
+            let result = check_follow(instance, &follower, &target_user).await?;
 
-            println!("{}", result);
+            if json_result {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "follower": follower,
+                        "user": target_user,
+                        "follows": result,
+                    })
+                );
+            } else {
+                println!("{}", result);
+            }
+        }
+        Command::CheckFollows { user } => {
+            let followers = read_delimited_usernames(std::io::stdin(), delimiter)?
+                .into_iter()
+                .map(|(username, _)| username)
+                .collect::<Vec<_>>();
+
+            // Resolve the target once rather than for every check
+            let target_user = match user {
+                Some(value) => value,
+                None => instance.current().user().await?.login,
+            };
+            let target_user = target_user.as_str();
+            let client = &client;
+
+            let results = stream::iter(followers)
+                .map(|follower| async move {
+                    let follows = client.check_follow(&follower, target_user).await?;
+                    Ok::<_, octocrab::Error>((follower, follows))
+                })
+                .buffered(client.config().concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let mut writer = csv_writer(delimiter);
+
+            for (follower, follows) in results {
+                writer.write_record(&[follower, follows.to_string()])?;
+            }
+        }
+        Command::WhoAmI => {
+            let user = client.current_user().await?;
+
+            if json_result {
+                println!("{}", serde_json::to_string(&user)?);
+            } else {
+                println!("{}", user.base.login);
+                println!("{} followers, {} following", user.followers, user.following);
+            }
         }
+        Command::RateLimit { watch: None } => {
+            println!(
+                "{}",
+                format_rate_limit(&instance.ratelimit().get().await?, Utc::now())
+            );
+        }
+        Command::RateLimit {
+            watch: Some(interval),
+        } => {
+            // Refresh a single line when writing to a terminal
+            let is_terminal = std::io::stdout().is_terminal();
+            // Created once so that an interrupt during a request is seen by the next wait
+            let interrupted = tokio::signal::ctrl_c();
+            futures::pin_mut!(interrupted);
+
+            loop {
+                let line = format_rate_limit(&instance.ratelimit().get().await?, Utc::now());
+
+                if is_terminal {
+                    print!("\r{}\x1b[K", line);
+                    std::io::stdout().flush()?;
+                } else {
+                    println!("{}", line);
+                }
+
+                tokio::select! {
+                    result = &mut interrupted => {
+                        result?;
+
+                        // Terminate the refreshed line before exiting
+                        if is_terminal {
+                            println!();
+                        }
+                        break;
+                    }
+                    _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                }
+            }
+        }
+        Command::ValidateToken { require } => match scopes::get_token_scopes(instance).await? {
+            Some(granted) => {
+                for scope in &granted {
+                    println!("{}", scope);
+                }
+
+                for (scope, purpose) in scopes::KNOWN_REQUIREMENTS {
+                    if !scopes::has_scope(&granted, scope) && !opts.quiet {
+                        eprintln!("Missing the {} scope (needed for {})", scope, purpose);
+                    }
+                }
+
+                for scope in require {
+                    scopes::require_scope(&granted, &scope)?;
+                }
+            }
+            None => {
+                if !require.is_empty() {
+                    return Err(format!(
+                        "GitHub did not report scopes for this token (it may be fine-grained), so \
+                         the required scopes can't be checked: {}",
+                        require.join(", ")
+                    )
+                    .into());
+                }
+
+                if !opts.quiet {
+                    eprintln!(
+                        "GitHub did not report scopes for this token (it may be fine-grained)"
+                    );
+                }
+            }
+        },
     }
 
     Ok(())
@@ -214,49 +680,211 @@ struct Opts {
     /// A GitHub personal access token (not needed for all operations)
     #[clap(short, long)]
     token: Option<String>,
+    /// The API base URL (e.g. "https://github.example.com/api/v3/" for GitHub Enterprise), which
+    /// defaults to the GITHUB_API_URL environment variable or GitHub's public API
+    #[clap(long, global = true)]
+    base_url: Option<String>,
     /// Logging verbosity
     #[clap(long, short = 'v', global = true, action = clap::ArgAction::Count)]
-    verbose: i32,
+    verbose: u8,
+    /// Only log errors (regardless of verbosity)
+    #[clap(long, short = 'q', global = true)]
+    quiet: bool,
+    /// How many times to retry failed requests (0 means no retries)
+    #[clap(long, global = true, default_value = "3")]
+    retries: u32,
+    /// How long to wait before the first retry (doubling after each attempt)
+    #[clap(long, global = true, default_value = "5000")]
+    retry_base_delay_ms: u64,
+    /// How many pages to fetch ahead for paginated listings (0 means no prefetching)
+    #[clap(long, global = true, default_value = "0")]
+    prefetch_pages: usize,
+    /// The maximum number of requests to have in flight at once
+    #[clap(short, long, global = true, default_value = "8", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+    /// How long to wait for each REST request (no limit if not provided)
+    #[clap(long, global = true)]
+    rest_timeout_ms: Option<u64>,
+    /// How long to wait for each GraphQL query (no limit if not provided)
+    #[clap(long, global = true)]
+    graphql_timeout_ms: Option<u64>,
+    /// The field delimiter for CSV input and output (a single printable ASCII character or a tab,
+    /// but not the quote character)
+    #[clap(long, global = true, default_value = ",", value_parser = parse_csv_delimiter)]
+    csv_delimiter: u8,
+    /// Skip users whose GraphQL queries still fail after retrying (instead of exiting)
+    #[clap(long, global = true)]
+    continue_on_error: bool,
+    /// Output format for user listings (CSV by default), or json (or jsonl) for check-follow and
+    /// whoami (plain text by default)
+    #[clap(long, global = true, value_enum)]
+    output_format: Option<ListFormat>,
+    /// Show progress on stderr for listings and contributor reports (only when stderr is a terminal
+    /// and --quiet isn't set)
+    #[clap(long, global = true)]
+    progress: bool,
     #[clap(subcommand)]
     command: Command,
 }
 
 #[derive(Parser)]
 enum Command {
-    /// Block a list of users provided in CSV format to stdin
+    /// Block a list of users provided in CSV (or JSON) format to stdin
     BlockUsers {
-        /// The organization to block users from (instead of the authenticated user)
-        #[clap(long)]
-        org: Option<String>,
-        /// Force block requests for all provided accounts (skip checking current block list)
+        /// Deprecated alias for --skip-known-check
         #[clap(long)]
         force: bool,
+        /// The input format (JSON input is a list of objects with a login and optional reason)
+        #[clap(long, value_enum, default_value = "csv")]
+        input_format: BlockInputFormat,
+        #[clap(flatten)]
+        block: BlockArgs,
+    },
+    /// Block every follower of a user, printing the blocked logins in CSV format to stdout
+    BlockFollowersOf {
+        /// The user whose followers should be blocked
+        user: String,
+        /// Don't ask for confirmation before blocking
+        #[clap(long, short)]
+        yes: bool,
+        #[clap(flatten)]
+        block: BlockArgs,
     },
     /// List the authenticated user's followers in CSV format to stdout
-    ListFollowers,
+    ListFollowers {
+        /// A file of previously seen follower IDs (only new followers are listed, and the file is updated)
+        #[clap(long)]
+        since: Option<String>,
+        /// Resume an interrupted listing from the page URL it reported
+        #[clap(long, conflicts_with = "since")]
+        resume_from: Option<String>,
+        /// Include account age in days as an additional column (requires additional requests)
+        #[clap(long, conflicts_with = "since")]
+        with_age: bool,
+        /// Only list followers with at least this many followers of their own (requires a GraphQL
+        /// query per page of followers)
+        #[clap(long, conflicts_with = "since")]
+        min_followers: Option<u64>,
+        /// Only list followers with at most this many followers of their own (requires a GraphQL
+        /// query per page of followers)
+        #[clap(long, conflicts_with = "since")]
+        max_followers: Option<u64>,
+        /// Suppress duplicate rows (by user ID), which can result from the list changing while paging
+        #[clap(long)]
+        dedupe_output: bool,
+    },
     /// List accounts the authenticated user follows in CSV format to stdout
-    ListFollowing,
+    ListFollowing {
+        /// Resume an interrupted listing from the page URL it reported
+        #[clap(long)]
+        resume_from: Option<String>,
+        /// Suppress duplicate rows (by user ID), which can result from the list changing while paging
+        #[clap(long)]
+        dedupe_output: bool,
+    },
     /// List accounts the authenticated user blocks in CSV format to stdout
     ListBlocks {
         /// The organization to list blocks for (instead of the authenticated user)
         #[clap(long)]
         org: Option<String>,
+        /// Resume an interrupted listing from the page URL it reported
+        #[clap(long)]
+        resume_from: Option<String>,
+        /// Include account age in days as an additional column (requires additional requests)
+        #[clap(long)]
+        with_age: bool,
+        /// Include the account creation date as an additional column (requires a GraphQL query per
+        /// page of blocks)
+        #[clap(long)]
+        with_created_at: bool,
+        /// Don't check that the authenticated user belongs to the organization before starting
+        #[clap(long, requires = "org")]
+        skip_org_check: bool,
+        /// Suppress duplicate rows (by user ID), which can result from the list changing while paging
+        #[clap(long)]
+        dedupe_output: bool,
     },
-    /// List PR contributors for the given repository
+    /// List PR contributors for the given repository (or repositories)
+    #[clap(group(clap::ArgGroup::new("repos").required(true).multiple(true)))]
     ListPrContributors {
         /// The repository to check for pull requests
+        #[clap(short, long, group = "repos")]
+        repo_path: Option<String>,
+        /// A file listing repositories (one per line) to check for pull requests
+        #[clap(long, group = "repos")]
+        repos_file: Option<String>,
+        /// Only count pull requests targeting this base branch
+        #[clap(long)]
+        base: Option<String>,
+        #[clap(flatten)]
+        report: ReportOptions,
+    },
+    /// List PR contributors across all repositories for the given organization
+    ListOrgContributors {
+        /// The organization whose repositories should be checked for pull requests
+        #[clap(long)]
+        org: String,
+        /// Skip archived repositories
+        #[clap(long)]
+        exclude_archived: bool,
+        #[clap(flatten)]
+        report: ReportOptions,
+    },
+    /// List exclusions for a repository whose users have no pull requests there in CSV format to stdout
+    PruneExclusions {
+        /// The repository whose exclusions should be checked
         #[clap(short, long)]
         repo_path: String,
-        /// Omit Twitter handle (which is not verified)
-        #[clap(long)]
-        omit_twitter: bool,
         /// Exclusions file
         #[clap(short, long, default_value = "data/exclusions.csv")]
         exclusions_file: String,
-        /// Ignore exclusions
+        /// Remove the stale exclusions from the file (which is rewritten sorted and lowercased)
         #[clap(long)]
-        ignore_exclusions: bool,
+        write: bool,
     },
+    /// List an organization's repositories that a user has opened pull requests against (with counts)
+    UserContributions {
+        /// The user whose pull requests should be counted
+        #[clap(short, long)]
+        user: String,
+        /// The organization whose repositories should be checked
+        #[clap(long)]
+        org: String,
+    },
+    /// List accounts that have starred the given repository in CSV format to stdout
+    ListStargazers {
+        /// The repository to list stargazers for
+        #[clap(short, long)]
+        repo_path: String,
+        /// Include when each account starred the repository as an additional column
+        #[clap(long)]
+        starred_at: bool,
+    },
+    /// Write the raw pull requests for the given repository as JSON Lines to stdout
+    DumpPullRequests {
+        /// The repository to dump pull requests for
+        #[clap(short, long)]
+        repo_path: String,
+        /// Only include pull requests in this state
+        #[clap(long, value_enum, default_value = "all")]
+        state: PrState,
+    },
+    /// List followers of the authenticated user who they don't follow back in CSV format to stdout
+    PendingFollowBacks {
+        /// Include account age in days as an additional column (requires additional requests)
+        #[clap(long)]
+        with_age: bool,
+    },
+    /// Show the author of a single pull request in CSV format to stdout
+    PrAuthor {
+        /// The pull request (e.g. "owner/repo#123"), or the repository if --number is provided
+        pr_path: String,
+        /// The pull request number
+        #[clap(short, long)]
+        number: Option<u64>,
+    },
+    /// Show follow relationships with the authenticated user for logins provided in CSV format to stdin
+    FollowStatus,
     /// Check whether one user follows another
     CheckFollow {
         /// The possibly followed user
@@ -266,6 +894,273 @@ enum Command {
         #[clap(short, long)]
         follower: String,
     },
+    /// Check whether each login provided in CSV format to stdin follows a user (in CSV format to stdout)
+    CheckFollows {
+        /// The possibly followed user (the authenticated user if not provided)
+        #[clap(short, long)]
+        user: Option<String>,
+    },
+    /// Show the remaining REST and GraphQL rate limits
+    RateLimit {
+        /// Keep polling with this interval in seconds (checking the rate limit doesn't count against it)
+        #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+        watch: Option<u64>,
+    },
+    /// Print the scopes granted to the token and warn about missing ones
+    ValidateToken {
+        /// Fail unless the token has this scope (may be repeated)
+        #[clap(long)]
+        require: Vec<String>,
+    },
+    /// Show the authenticated user
+    #[clap(name = "whoami")]
+    WhoAmI,
+}
+
+impl Command {
+    /// Check that the command supports the requested output format, and whether a command that
+    /// prints a single result should print it as JSON (instead of plain text)
+    fn uses_json_result(&self, format: Option<ListFormat>) -> Result<bool, String> {
+        match (self, format) {
+            (_, None) => Ok(false),
+            (
+                Command::ListFollowers { .. }
+                | Command::ListFollowing { .. }
+                | Command::ListBlocks { .. }
+                | Command::ListStargazers { .. }
+                | Command::PendingFollowBacks { .. },
+                Some(_),
+            ) => Ok(false),
+            (
+                Command::CheckFollow { .. } | Command::WhoAmI,
+                Some(ListFormat::Json | ListFormat::Jsonl),
+            ) => Ok(true),
+            (Command::CheckFollow { .. } | Command::WhoAmI, Some(_)) => {
+                Err("check-follow and whoami only support json or jsonl output".to_string())
+            }
+            _ => Err("--output-format isn't supported by this command".to_string()),
+        }
+    }
+}
+
+/// Options shared by the blocking commands
+#[derive(clap::Args)]
+struct BlockArgs {
+    /// The organization to block users from (instead of the authenticated user)
+    #[clap(long)]
+    org: Option<String>,
+    /// Request a block for every account (skip checking the current block list)
+    #[clap(long)]
+    skip_known_check: bool,
+    /// A file of logins (one per line) that should never be blocked
+    #[clap(long)]
+    protect_file: Option<String>,
+    /// A local CSV file to append login, reason (from the optional second input column for
+    /// block-users), and timestamp to for each new block
+    #[clap(long)]
+    reasons_file: Option<String>,
+    /// Never block accounts that the authenticated user follows
+    #[clap(long)]
+    exclude_following: bool,
+    /// Don't check that the authenticated user belongs to the organization before starting
+    #[clap(long, requires = "org")]
+    skip_org_check: bool,
+    /// Print the accounts that would be blocked without blocking them
+    #[clap(long)]
+    dry_run: bool,
+    /// Exit with an error status if any accounts weren't found (as well as if any blocks failed)
+    #[clap(long)]
+    strict: bool,
+    /// Wait for the rate limit to reset once fewer than this many requests remain (0 disables
+    /// pacing)
+    #[clap(long, default_value = "10")]
+    pace_below: u32,
+    /// Wait this many milliseconds between block requests
+    #[clap(long)]
+    delay_ms: Option<u64>,
+}
+
+impl BlockArgs {
+    /// Build the block options (loading the protected logins, if provided)
+    fn to_block_options(
+        &self,
+        retry: RetryConfig,
+        delimiter: u8,
+    ) -> Result<BlockOptions, Box<dyn std::error::Error>> {
+        let protected = match &self.protect_file {
+            Some(path) => read_protected_logins(File::open(path)?)?,
+            None => HashSet::new(),
+        };
+
+        Ok(BlockOptions {
+            skip_known_check: self.skip_known_check,
+            retry,
+            protected,
+            exclude_following: self.exclude_following,
+            audit_file: self.reasons_file.as_ref().map(Into::into),
+            pace_below: self.pace_below,
+            csv_delimiter: Some(delimiter),
+            delay: self.delay_ms.map(Duration::from_millis),
+            dry_run: self.dry_run,
+        })
+    }
+}
+
+/// Options shared by the contributor report commands
+#[derive(clap::Args)]
+struct ReportOptions {
+    /// Omit Twitter handle (which is not verified)
+    #[clap(long)]
+    omit_twitter: bool,
+    /// Exclusions file
+    #[clap(short, long, default_value = "data/exclusions.csv")]
+    exclusions_file: String,
+    /// Ignore exclusions
+    #[clap(long)]
+    ignore_exclusions: bool,
+    /// Only include pull requests in this state
+    #[clap(long, value_enum, default_value = "all")]
+    state: PrState,
+    /// Only include pull requests that were closed without being merged (the same as
+    /// `--state closed-unmerged`)
+    #[clap(long, conflicts_with = "state")]
+    closed_unmerged: bool,
+    /// Group contributors by user ID (merging renamed accounts) instead of by login
+    #[clap(long)]
+    group_by_id: bool,
+    /// Include public email and bio columns (requires one additional request per contributor)
+    #[clap(long)]
+    contact_info: bool,
+    /// How to sort the report
+    #[clap(long, value_enum, default_value = "login")]
+    sort: ContributorSort,
+    /// Reverse the sort order
+    #[clap(long)]
+    reverse: bool,
+    /// The number of users to request in each GraphQL query
+    #[clap(long, default_value = "50", value_parser = clap::value_parser!(u16).range(1..))]
+    graphql_chunk_size: u16,
+    /// Include the most privileged author association (e.g. MEMBER or FIRST_TIME_CONTRIBUTOR) seen
+    /// across each contributor's pull requests as a final column
+    #[clap(long)]
+    author_association: bool,
+    /// Include follower and following count columns (requires a token)
+    #[clap(long)]
+    follow_counts: bool,
+    /// Add a column flagging young accounts with few followers (requires a token)
+    #[clap(long)]
+    suspicious: bool,
+    /// Accounts younger than this many days at their first pull request may be suspicious
+    #[clap(long, default_value = "30", requires = "suspicious")]
+    suspicious_max_age_days: i64,
+    /// Accounts with fewer followers than this may be suspicious
+    #[clap(long, default_value = "5", requires = "suspicious")]
+    suspicious_min_followers: u64,
+}
+
+impl ReportOptions {
+    /// Whether an account was young at its first pull request and has few followers (accounts
+    /// we have no information about are never flagged)
+    fn is_suspicious(&self, age: i64, followers: Option<u64>) -> bool {
+        age >= 0
+            && age < self.suspicious_max_age_days
+            && followers.is_some_and(|followers| followers < self.suspicious_min_followers)
+    }
+
+    fn pr_state(&self) -> PrState {
+        if self.closed_unmerged {
+            PrState::ClosedUnmerged
+        } else {
+            self.state
+        }
+    }
+
+    fn load_exclusions(&self, delimiter: u8) -> Result<Exclusions, Box<dyn std::error::Error>> {
+        if self.ignore_exclusions {
+            Ok(Exclusions::default())
+        } else {
+            let file = File::open(&self.exclusions_file)?;
+            Ok(Exclusions::load_with_delimiter(file, delimiter)?)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ContributorSort {
+    /// Alphabetically by login
+    Login,
+    /// By number of pull requests (most first)
+    Count,
+    /// By account age at the time of the first pull request (youngest first, requires a token)
+    Age,
+    /// By the date of the first pull request (earliest first)
+    FirstPr,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum BlockInputFormat {
+    Csv,
+    Json,
+    Jsonl,
+}
+
+impl From<BlockInputFormat> for InputFormat {
+    fn from(format: BlockInputFormat) -> Self {
+        match format {
+            BlockInputFormat::Csv => InputFormat::Csv,
+            BlockInputFormat::Json => InputFormat::Json,
+            BlockInputFormat::Jsonl => InputFormat::Jsonl,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ListFormat {
+    Csv,
+    Json,
+    Jsonl,
+    Markdown,
+}
+
+impl From<ListFormat> for output::OutputFormat {
+    fn from(format: ListFormat) -> Self {
+        match format {
+            ListFormat::Csv => output::OutputFormat::Csv,
+            ListFormat::Json => output::OutputFormat::Json,
+            ListFormat::Jsonl => output::OutputFormat::Jsonl,
+            ListFormat::Markdown => output::OutputFormat::Markdown,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum PrState {
+    Open,
+    Closed,
+    All,
+    Merged,
+    ClosedUnmerged,
+}
+
+impl PrState {
+    /// GitHub's pull request state doesn't distinguish merged from closed, so we filter locally
+    fn includes(&self, pr: &PullRequest) -> bool {
+        match self {
+            PrState::Merged => pr.merged_at.is_some(),
+            PrState::ClosedUnmerged => pr.closed_at.is_some() && pr.merged_at.is_none(),
+            _ => true,
+        }
+    }
+}
+
+impl From<PrState> for State {
+    fn from(state: PrState) -> Self {
+        match state {
+            PrState::Open => State::Open,
+            PrState::Closed | PrState::Merged | PrState::ClosedUnmerged => State::Closed,
+            PrState::All => State::All,
+        }
+    }
 }
 
 struct AdditionalUserInfo {
@@ -274,34 +1169,820 @@ struct AdditionalUserInfo {
     user_info: HashMap<String, UserInfo>,
 }
 
-async fn load_additional_user_info(
+/// Summarize the remaining core and GraphQL rate limits and the time until they reset
+fn format_rate_limit(rate_limit: &RateLimit, now: DateTime<Utc>) -> String {
+    let format_rate = |rate: &Rate| {
+        let until_reset = (rate.reset as i64 - now.timestamp()).max(0);
+        format!(
+            "{}/{} remaining (resets in {}s)",
+            rate.remaining, rate.limit, until_reset
+        )
+    };
+
+    let graphql = rate_limit
+        .resources
+        .graphql
+        .as_ref()
+        .map_or_else(|| "unavailable".to_string(), format_rate);
+
+    format!(
+        "core: {}, graphql: {}",
+        format_rate(&rate_limit.resources.core),
+        graphql
+    )
+}
+
+/// Fail early if the authenticated user doesn't belong to the given organization (unless skipped)
+async fn check_org(client: &OctocrabbyClient, org: Option<&str>, skip: bool) -> Void {
+    if let (Some(org), false) = (org, skip) {
+        let membership = client.org_membership(org).await.inspect_err(|_| {
+            log::error!("Organization check failed; pass --skip-org-check to continue anyway");
+        })?;
+        log::info!("Authenticated as a {} of {}", membership.role, org);
+    }
+
+    Ok(())
+}
+
+/// Ask a yes-or-no question on stderr (which requires stdin to be a terminal)
+fn confirm(prompt: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    if !std::io::stdin().is_terminal() {
+        return Err("Confirmation requires an interactive terminal (use --yes to skip it)".into());
+    }
+
+    eprint!("{} [y/N] ", prompt);
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// A CSV writer for stdout with the configured delimiter
+fn csv_writer(delimiter: u8) -> csv::Writer<std::io::Stdout> {
+    csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(std::io::stdout())
+}
+
+/// Parse a CSV delimiter, which must be a single printable ASCII character (or a tab) other than
+/// the quote character
+fn parse_csv_delimiter(value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        [b'"'] => Err("can't be the quote character".to_string()),
+        [byte] if byte.is_ascii_graphic() || *byte == b' ' || *byte == b'\t' => Ok(*byte),
+        _ => Err("must be a single printable ASCII character or a tab".to_string()),
+    }
+}
+
+/// A progress display on stderr: a bar when the number of pages is known (from the `last` link of
+/// the first page), and otherwise a spinner with a running count
+///
+/// Nothing is shown unless stderr is a terminal, so logs and redirected output aren't affected.
+struct Progress {
+    bar: ProgressBar,
+    pages: u64,
+    items: usize,
+}
+
+impl Progress {
+    const SPINNER_TEMPLATE: &'static str = "{spinner} {msg}";
+    const BAR_TEMPLATE: &'static str = "[{bar:30}] {pos}/{len} pages, {msg}";
+
+    fn new(requested: bool) -> Self {
+        let bar = if requested && std::io::stderr().is_terminal() {
+            let bar = ProgressBar::new_spinner();
+            if let Ok(style) = ProgressStyle::with_template(Self::SPINNER_TEMPLATE) {
+                bar.set_style(style);
+            }
+            bar
+        } else {
+            ProgressBar::hidden()
+        };
+
+        Progress {
+            bar,
+            pages: 0,
+            items: 0,
+        }
+    }
+
+    fn advance_page<T>(&mut self, page: &Page<T>) {
+        if self.pages == 0 {
+            if let Some(total) = page.number_of_pages() {
+                self.bar.set_length(total.into());
+                if let Ok(style) = ProgressStyle::with_template(Self::BAR_TEMPLATE) {
+                    self.bar.set_style(style.progress_chars("#> "));
+                }
+            }
+        }
+
+        self.pages += 1;
+        self.bar.set_position(self.pages);
+        self.advance(page.items.len());
+    }
+
+    fn advance(&mut self, items: usize) {
+        self.items += items;
+        self.bar.set_message(format!("{} items", self.items));
+        self.bar.tick();
+    }
+
+    /// Leave the final state on its own line so that later output starts on a new line
+    fn finish(&self) {
+        if self.items > 0 {
+            self.bar.finish();
+        } else {
+            self.bar.finish_and_clear();
+        }
+    }
+}
+
+/// Print a record count to stderr once a listing completes (unless quiet)
+fn report_count(quiet: bool, count: usize, description: &str) {
+    if !quiet {
+        eprintln!("{} {}", count, description);
+    }
+}
+
+/// Log the number of duplicate rows skipped because of `--dedupe-output`
+fn report_duplicates(dedupe_output: bool, duplicates: usize) {
+    if duplicates > 0 {
+        log::warn!("Suppressed {} duplicate rows", duplicates);
+    } else if dedupe_output {
+        log::info!("No duplicate rows found");
+    }
+}
+
+/// Optional enrichment and filtering for user listings (which require a GraphQL query per page)
+#[derive(Clone, Copy, Default)]
+struct UserListOptions {
+    /// Add the account age in days as a third column (with -1 for accounts that couldn't be found)
+    with_age: bool,
+    /// Add the account creation date as another column (empty for accounts that couldn't be found)
+    with_created_at: bool,
+    min_followers: Option<u64>,
+    max_followers: Option<u64>,
+    /// Skip users whose IDs have already been printed
+    dedupe_output: bool,
+    /// Show progress on stderr
+    progress: bool,
+}
+
+impl UserListOptions {
+    fn needs_user_info(&self) -> bool {
+        self.with_age
+            || self.with_created_at
+            || self.min_followers.is_some()
+            || self.max_followers.is_some()
+    }
+
+    /// Accounts whose follower counts are unknown are excluded if any bound is set
+    fn includes(&self, info: Option<&UserInfo>) -> bool {
+        if self.min_followers.is_none() && self.max_followers.is_none() {
+            return true;
+        }
+
+        match info.and_then(|info| info.followers) {
+            Some(followers) => {
+                self.min_followers
+                    .is_none_or(|min| followers.total_count >= min)
+                    && self
+                        .max_followers
+                        .is_none_or(|max| followers.total_count <= max)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Print users page by page in the selected output format, printing a resume URL to stderr if
+/// the listing is interrupted
+///
+/// Returns the number of users printed.
+async fn print_user_pages<S: Stream<Item = octocrabby::error::Result<Page<User>>>>(
+    client: &OctocrabbyClient,
+    pages: S,
+    options: UserListOptions,
+    mut sink: Box<dyn RecordSink>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    futures::pin_mut!(pages);
+    let mut progress = Progress::new(options.progress);
+    let mut resume_from = None;
+    let mut count = 0;
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let now = Utc::now();
+
+    loop {
+        let result = match pages.try_next().await {
+            Ok(Some(page)) if options.needs_user_info() => load_page_user_info(client, &page)
+                .await
+                .map(|user_info| Some((page, Some(user_info))))
+                .map_err(Into::into),
+            Ok(page) => Ok(page.map(|page| (page, None))),
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(Some((mut page, user_info))) => {
+                progress.advance_page(&page);
+
+                for user in page.take_items() {
+                    let info = user_info
+                        .as_ref()
+                        .and_then(|user_info| user_info.get(&user.login));
+
+                    if !options.includes(info) {
+                        continue;
+                    }
+
+                    if options.dedupe_output && !seen.insert(user.id) {
+                        duplicates += 1;
+                        continue;
+                    }
+
+                    let age = options
+                        .with_age
+                        .then(|| info.map_or(-1, |info| (now - info.created_at).num_days()));
+
+                    let created_at = options
+                        .with_created_at
+                        .then(|| info.map(|info| info.created_at));
+
+                    sink.write_record(&OutputRecord {
+                        age,
+                        created_at,
+                        ..OutputRecord::new(&user)
+                    })?;
+                    count += 1;
+                }
+                resume_from = page.next;
+            }
+            Ok(None) => {
+                progress.finish();
+                sink.finish()?;
+                report_duplicates(options.dedupe_output, duplicates);
+                return Ok(count);
+            }
+            Err(error) => {
+                progress.finish();
+                // Finish even after an error, since some formats buffer the records already seen
+                sink.finish()?;
+
+                if let Some(url) = resume_from {
+                    // Printed directly (not logged) so that it's shown at the default verbosity
+                    eprintln!("Listing interrupted; continue with --resume-from {}", url);
+                }
+                return Err(error.into());
+            }
+        }
+    }
+}
+
+/// Load additional information about the accounts on a page (via GraphQL)
+async fn load_page_user_info(
+    client: &OctocrabbyClient,
+    page: &Page<User>,
+) -> octocrab::Result<HashMap<String, UserInfo>> {
+    let usernames = page
+        .items
+        .iter()
+        .map(|user| user.login.as_str())
+        .collect::<Vec<_>>();
+
+    client
+        .users_info(&usernames)
+        .map_ok(|info| (info.login.clone(), info))
+        .try_collect()
+        .await
+}
+
+/// Load extended profiles (including public email and bio) for users via individual REST requests
+async fn load_contact_info(
     instance: &Octocrab,
     usernames: &[&str],
-) -> octocrab::Result<AdditionalUserInfo> {
+    concurrency: usize,
+) -> octocrab::Result<HashMap<String, ExtendedUser>> {
+    log::warn!(
+        "Loading contact information requires {} additional requests",
+        usernames.len()
+    );
+
+    octocrabby::get_users_rest(instance, usernames, concurrency)
+        .map_ok(|user| (user.base.login.clone(), user))
+        .try_collect()
+        .await
+}
+
+/// Read repository paths from a file (one per line), skipping blank lines, comments, and invalid paths
+fn read_repo_paths(path: &str) -> std::io::Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| {
+            let is_valid = parse_repo_path(line).is_some();
+            if !is_valid {
+                log::warn!("Skipping invalid repository path: {}", line);
+            }
+            is_valid
+        })
+        .map(str::to_string)
+        .collect())
+}
+
+/// Count the pull requests for a repository as they arrive, omitting those opened by excluded users
+#[allow(clippy::too_many_arguments)]
+async fn count_contributor_prs(
+    client: &OctocrabbyClient,
+    owner: &str,
+    repo: &str,
+    state: PrState,
+    base: Option<&str>,
+    exclusions: &Exclusions,
+    counts: &mut ContributorCounts,
+    progress: &mut Progress,
+) -> octocrab::Result<()> {
+    let repo_path = format!("{}/{}", owner, repo);
+    let mut excluded = BTreeSet::new();
+
+    client
+        .pull_requests_with_base(owner, repo, state.into(), base)
+        .try_filter(|pr| future::ready(state.includes(pr)))
+        .try_for_each(|pr| {
+            progress.advance(1);
+
+            match pr.user.as_ref() {
+                Some(user) if exclusions.is_excluded(&repo_path, &user.login) => {
+                    excluded.insert(user.login.clone());
+                }
+                _ => counts.add(&pr),
+            }
+            future::ok(())
+        })
+        .await?;
+
+    for username in excluded {
+        log::warn!("Excluded user {}", username);
+    }
+
+    Ok(())
+}
+
+/// Count a user's pull requests for each of an organization's repositories, omitting repositories
+/// without any (sorted by repository name)
+async fn count_user_prs_by_repo(
+    client: &OctocrabbyClient,
+    org: &str,
+    user: &str,
+) -> octocrab::Result<Vec<(String, usize)>> {
+    log::info!("Loading repositories for {}", org);
+    let repos = client
+        .org_repos(org)
+        .map_ok(|repo| repo.name)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut counts = stream::iter(repos)
+        .map(|repo| async move {
+            log::info!("Loading pull requests for {}/{}", org, repo);
+            let count = client
+                .pull_requests(org, &repo, State::All)
+                .try_filter(|pr| {
+                    future::ready(
+                        pr.user
+                            .as_ref()
+                            .is_some_and(|author| author.login.eq_ignore_ascii_case(user)),
+                    )
+                })
+                .try_fold(0, |count, _| future::ok(count + 1))
+                .await?;
+
+            Ok::<_, octocrab::Error>((repo, count))
+        })
+        .buffer_unordered(client.config().concurrency)
+        .try_filter(|(_, count)| future::ready(*count > 0))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    counts.sort_unstable();
+
+    Ok(counts)
+}
+
+struct Contributor {
+    username: String,
+    user_id: UserId,
+    pr_count: usize,
+    first_pr_date: DateTime<Utc>,
+    last_pr_date: DateTime<Utc>,
+    /// The most privileged association seen across the contributor's pull requests
+    author_association: Option<String>,
+}
+
+/// Author associations from most to least privileged
+const AUTHOR_ASSOCIATIONS: [&str; 8] = [
+    "OWNER",
+    "MEMBER",
+    "COLLABORATOR",
+    "CONTRIBUTOR",
+    "FIRST_TIME_CONTRIBUTOR",
+    "FIRST_TIMER",
+    "MANNEQUIN",
+    "NONE",
+];
+
+/// Lower values are more privileged (unknown associations are ranked last)
+fn author_association_rank(association: &str) -> usize {
+    AUTHOR_ASSOCIATIONS
+        .iter()
+        .position(|value| *value == association)
+        .unwrap_or(AUTHOR_ASSOCIATIONS.len())
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum ContributorKey {
+    Login(String),
+    Id(UserId),
+}
+
+/// Pull request counts by author, accumulated as pull requests are loaded (so that only the
+/// aggregate is retained)
+struct ContributorCounts {
+    group_by_id: bool,
+    contributors: HashMap<ContributorKey, Contributor>,
+}
+
+impl ContributorCounts {
+    /// Group by user ID (merging renamed accounts) instead of by login
+    fn new(group_by_id: bool) -> Self {
+        ContributorCounts {
+            group_by_id,
+            contributors: HashMap::new(),
+        }
+    }
+
+    /// When grouping by ID, the login from the most recent pull request is used
+    fn add(&mut self, pr: &PullRequest) {
+        let (user, created_at) = match (pr.user.as_ref(), pr.created_at) {
+            (Some(user), Some(created_at)) => (user, created_at),
+            _ => return,
+        };
+
+        let key = if self.group_by_id {
+            ContributorKey::Id(user.id)
+        } else {
+            ContributorKey::Login(user.login.clone())
+        };
+
+        match self.contributors.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let contributor = entry.get_mut();
+                contributor.pr_count += 1;
+                contributor.first_pr_date = contributor.first_pr_date.min(created_at);
+
+                if created_at >= contributor.last_pr_date {
+                    contributor.last_pr_date = created_at;
+                    contributor.username = user.login.clone();
+                }
+
+                if let Some(association) = pr.author_association.as_ref() {
+                    let is_more_privileged =
+                        contributor
+                            .author_association
+                            .as_ref()
+                            .is_none_or(|current| {
+                                author_association_rank(association)
+                                    < author_association_rank(current)
+                            });
+
+                    if is_more_privileged {
+                        contributor.author_association = Some(association.clone());
+                    }
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Contributor {
+                    username: user.login.clone(),
+                    user_id: user.id,
+                    pr_count: 1,
+                    first_pr_date: created_at,
+                    last_pr_date: created_at,
+                    author_association: pr.author_association.clone(),
+                });
+            }
+        }
+    }
+
+    /// The contributors, sorted by login (or by ID)
+    fn into_contributors(self) -> Vec<Contributor> {
+        let mut contributors = self.contributors.into_iter().collect::<Vec<_>>();
+        contributors.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+        contributors
+            .into_iter()
+            .map(|(_, contributor)| contributor)
+            .collect()
+    }
+}
+
+/// Sort contributors (stably, so that ties remain in login order)
+///
+/// When sorting by age, contributors whose accounts couldn't be found go last in either direction.
+fn sort_contributors(
+    results: &mut [Contributor],
+    sort: ContributorSort,
+    reverse: bool,
+    user_info: Option<&HashMap<String, UserInfo>>,
+) {
+    let sort = if sort == ContributorSort::Age && user_info.is_none() {
+        log::warn!("Sorting by age requires authentication; sorting by login");
+        ContributorSort::Login
+    } else {
+        sort
+    };
+
+    results.sort_by(|a, b| a.username.cmp(&b.username));
+
+    let age = |contributor: &Contributor| {
+        user_info
+            .and_then(|user_info| user_info.get(&contributor.username))
+            .map(|info| (contributor.first_pr_date - info.created_at).num_days())
+    };
+
+    results.sort_by(|a, b| {
+        let ordering = match sort {
+            ContributorSort::Login => a.username.cmp(&b.username),
+            ContributorSort::Count => b.pr_count.cmp(&a.pr_count),
+            ContributorSort::Age => match (age(a), age(b)) {
+                (Some(a_age), Some(b_age)) => a_age.cmp(&b_age),
+                (a_age, b_age) => return a_age.is_none().cmp(&b_age.is_none()),
+            },
+            ContributorSort::FirstPr => a.first_pr_date.cmp(&b.first_pr_date),
+        };
+
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// Write a contributor report for the aggregated pull request counts in CSV format to stdout
+async fn write_contributor_report(
+    client: &OctocrabbyClient,
+    counts: ContributorCounts,
+    report: &ReportOptions,
+    delimiter: u8,
+) -> Void {
+    let instance = client.instance();
+    let mut results = counts.into_contributors();
+
+    let usernames = results
+        .iter()
+        .map(|contributor| contributor.username.as_str())
+        .collect::<Vec<_>>();
+
+    // Load additional information that's only available if you're authenticated
+    let mut additional_info: Option<AdditionalUserInfo> = if instance.current().user().await.is_ok()
+    {
+        Some(load_additional_user_info(client, &usernames, report.graphql_chunk_size.into()).await?)
+    } else {
+        None
+    };
+
+    let mut contact_info = if report.contact_info {
+        Some(load_contact_info(instance, &usernames, client.config().concurrency).await?)
+    } else {
+        None
+    };
+
+    sort_contributors(
+        &mut results,
+        report.sort,
+        report.reverse,
+        additional_info.as_ref().map(|info| &info.user_info),
+    );
+
+    let mut writer = csv_writer(delimiter);
+
+    for Contributor {
+        username,
+        user_id,
+        pr_count,
+        first_pr_date,
+        author_association,
+        ..
+    } in results
+    {
+        let mut record = vec![username.clone(), user_id.to_string(), pr_count.to_string()];
+
+        // Add other fields to the record if you're authenticated
+        if let Some(AdditionalUserInfo {
+            ref follows_you,
+            ref you_follow,
+            ref mut user_info,
+        }) = additional_info
+        {
+            let (age, name, twitter_username, followers, following) =
+                match user_info.remove(&username) {
+                    Some(info) => (
+                        (first_pr_date - info.created_at).num_days(),
+                        info.name.unwrap_or_default(),
+                        info.twitter_username.unwrap_or_default(),
+                        info.followers.map(|followers| followers.total_count),
+                        info.following.map(|following| following.total_count),
+                    ),
+                    None => {
+                        // These values will be used for accounts such as dependabot
+                        (-1, "".to_string(), "".to_string(), None, None)
+                    }
+                };
+
+            record.push(age.to_string());
+            record.push(name);
+            if !report.omit_twitter {
+                record.push(twitter_username);
+            }
+            record.push(you_follow.contains(&username).to_string());
+            record.push(follows_you.contains(&username).to_string());
+
+            if report.follow_counts {
+                record.push(followers.map(|count| count.to_string()).unwrap_or_default());
+                record.push(following.map(|count| count.to_string()).unwrap_or_default());
+            }
+
+            if report.suspicious {
+                record.push(report.is_suspicious(age, followers).to_string());
+            }
+        }
+
+        if let Some(ref mut contact_info) = contact_info {
+            let (email, bio) = match contact_info.remove(&username) {
+                Some(user) => (user.email.unwrap_or_default(), user.bio.unwrap_or_default()),
+                None => ("".to_string(), "".to_string()),
+            };
+
+            record.push(email);
+            record.push(bio);
+        }
+
+        if report.author_association {
+            record.push(author_association.unwrap_or_default());
+        }
+
+        writer.write_record(&record)?;
+    }
+
+    Ok(())
+}
+
+/// Load a set of user IDs (one per line), treating a missing file as empty
+fn load_seen_ids(path: &str) -> Result<HashSet<u64>, Box<dyn std::error::Error>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Atomically replace a file of user IDs by writing to a temporary file and renaming it
+fn save_seen_ids(path: &str, ids: &HashSet<u64>) -> std::io::Result<()> {
+    let mut ids = ids.iter().collect::<Vec<_>>();
+    ids.sort_unstable();
+
+    let temp_path = format!("{}.tmp", path);
+    let mut contents = String::new();
+
+    for id in ids {
+        contents.push_str(&id.to_string());
+        contents.push('\n');
+    }
+
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(temp_path, path)
+}
+
+/// Load the logins of the authenticated user's followers and of accounts they follow
+async fn load_follow_sets(
+    client: &OctocrabbyClient,
+) -> octocrab::Result<(HashSet<String>, HashSet<String>)> {
     log::info!("Loading follower information");
-    let follows_you = octocrabby::get_followers(instance)
+    let follows_you = client
+        .followers()
         .and_then(|user| future::ok(user.login))
         .try_collect()
         .await?;
 
     log::info!("Loading following information");
-    let you_follow = octocrabby::get_following(instance)
+    let you_follow = client
+        .following()
         .and_then(|user| future::ok(user.login))
         .try_collect()
         .await?;
 
+    Ok((follows_you, you_follow))
+}
+
+/// Load the authenticated user's followers that they don't follow, sorted by login
+async fn load_pending_follow_backs(client: &OctocrabbyClient) -> octocrab::Result<Vec<User>> {
+    log::info!("Loading follower information");
+    let mut followers = client.followers().try_collect::<Vec<_>>().await?;
+
+    log::info!("Loading following information");
+    let you_follow = client
+        .following()
+        .and_then(|user| future::ok(user.login))
+        .try_collect::<HashSet<_>>()
+        .await?;
+
+    followers.retain(|user| !you_follow.contains(&user.login));
+    followers.sort_by(|a, b| a.login.cmp(&b.login));
+
+    Ok(followers)
+}
+
+/// Load the authenticated user's followers that they don't follow with account ages, sorted by login
+///
+/// The followers are loaded via GraphQL, which includes creation dates without additional requests.
+async fn load_pending_follow_backs_with_age(
+    client: &OctocrabbyClient,
+) -> Result<Vec<OutputRecord>, Box<dyn std::error::Error>> {
+    let login = client.current_user().await?.base.login;
+
+    log::info!("Loading follower information");
+    let mut followers = client
+        .followers_info(&login)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    log::info!("Loading following information");
+    let you_follow = client
+        .following()
+        .and_then(|user| future::ok(user.login))
+        .try_collect::<HashSet<_>>()
+        .await?;
+
+    followers.retain(|info| !you_follow.contains(&info.login));
+    followers.sort_by(|a, b| a.login.cmp(&b.login));
+
+    let now = Utc::now();
+
+    followers
+        .into_iter()
+        .map(|info| {
+            let id = info
+                .database_id
+                .ok_or_else(|| format!("No ID returned for {}", info.login))?;
+
+            Ok(OutputRecord {
+                login: info.login,
+                id,
+                age: Some((now - info.created_at).num_days()),
+                created_at: None,
+                starred_at: None,
+            })
+        })
+        .collect()
+}
+
+async fn load_additional_user_info(
+    client: &OctocrabbyClient,
+    usernames: &[&str],
+    chunk_size: usize,
+) -> octocrab::Result<AdditionalUserInfo> {
+    let (follows_you, you_follow) = load_follow_sets(client).await?;
+
     log::info!(
         "Loading additional user information for {} users",
         usernames.len()
     );
 
-    // For some reason the GraphQL endpoint often responds with 502s
-    let user_info: HashMap<String, UserInfo> = tryhard::retry_fn(|| {
-        octocrabby::get_users_info_chunked(instance, usernames, GRAPHQL_CHUNK_SIZE)
-            .try_collect::<Vec<_>>()
-    })
-    .retries(GRAPHQL_RETRIES)
-    .exponential_backoff(GRAPHQL_DELAY)
+    if chunk_size > GRAPHQL_LARGE_CHUNK_SIZE {
+        log::warn!(
+            "GraphQL chunk size {} may exceed GitHub's query complexity limits",
+            chunk_size
+        );
+    }
+
+    // For some reason the GraphQL endpoint often responds with 502s, so each chunk is retried
+    let user_info: HashMap<String, UserInfo> = octocrabby::get_users_info_with_fallback(
+        client.instance(),
+        usernames,
+        chunk_size,
+        client.graphql_retry(),
+        client.config().concurrency,
+        client.config().continue_on_error,
+    )
     .await?
     .into_iter()
     .map(|info| (info.login.clone(), info))
@@ -313,3 +1994,83 @@ async fn load_additional_user_info(
         user_info,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn contributor(username: &str, user_id: u64, first_pr_day: u32) -> Contributor {
+        let first_pr_date = Utc
+            .with_ymd_and_hms(2020, 6, first_pr_day, 0, 0, 0)
+            .unwrap();
+
+        Contributor {
+            username: username.to_string(),
+            user_id: UserId(user_id),
+            pr_count: 1,
+            first_pr_date,
+            last_pr_date: first_pr_date,
+            author_association: None,
+        }
+    }
+
+    fn user_info(login: &str, created_day: u32) -> UserInfo {
+        UserInfo {
+            login: login.to_string(),
+            created_at: Utc.with_ymd_and_hms(2020, 1, created_day, 0, 0, 0).unwrap(),
+            name: None,
+            twitter_username: None,
+            database_id: None,
+            followers: None,
+            following: None,
+        }
+    }
+
+    #[test]
+    fn csv_delimiter_accepts_printable_characters_and_tabs() {
+        assert_eq!(parse_csv_delimiter(","), Ok(b','));
+        assert_eq!(parse_csv_delimiter(";"), Ok(b';'));
+        assert_eq!(parse_csv_delimiter("\t"), Ok(b'\t'));
+    }
+
+    #[test]
+    fn csv_delimiter_rejects_quotes_line_breaks_and_control_characters() {
+        for value in ["\"", "\n", "\r", "\0", "\x1b", "\x7f", "", ",,", "é"] {
+            assert!(
+                parse_csv_delimiter(value).is_err(),
+                "Expected {:?} to be rejected",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn sort_by_age_puts_unknown_contributors_last() {
+        let user_info = vec![user_info("old", 1), user_info("young", 20)]
+            .into_iter()
+            .map(|info| (info.login.clone(), info))
+            .collect::<HashMap<_, _>>();
+
+        let sorted_logins = |reverse| {
+            let mut results = vec![
+                contributor("ghost", 1, 1),
+                contributor("old", 2, 1),
+                contributor("young", 3, 1),
+            ];
+            sort_contributors(
+                &mut results,
+                ContributorSort::Age,
+                reverse,
+                Some(&user_info),
+            );
+            results
+                .into_iter()
+                .map(|contributor| contributor.username)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted_logins(false), vec!["young", "old", "ghost"]);
+        assert_eq!(sorted_logins(true), vec!["old", "young", "ghost"]);
+    }
+}
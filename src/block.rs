@@ -0,0 +1,290 @@
+use super::{
+    block_user_with_headers, error, get_blocks_with_options, get_user_list,
+    read_usernames_in_format, with_timeout, BlockStatus, InputFormat, PagingOptions, RetryConfig,
+    StatusAndHeaders, BLOCKS_PAGE_SIZE, DEFAULT_CSV_DELIMITER, FOLLOWING_PAGE_SIZE,
+};
+use chrono::Utc;
+use futures::{future, TryStreamExt};
+use octocrab::Octocrab;
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Configuration for bulk blocking
+#[derive(Clone, Debug, Default)]
+pub struct BlockOptions {
+    /// Request a block for every account (skip checking the current block list)
+    pub skip_known_check: bool,
+    /// How many times to retry a user after being rate limited (and how long to wait if GitHub
+    /// doesn't say)
+    pub retry: RetryConfig,
+    /// Lowercased logins that must never be blocked
+    pub protected: HashSet<String>,
+    /// Skip accounts that the authenticated user follows
+    pub exclude_following: bool,
+    /// A local CSV file to append `login,reason,timestamp` rows to for each new block
+    pub audit_file: Option<PathBuf>,
+    /// Wait for the rate limit to reset once fewer than this many requests remain (zero disables
+    /// pacing, so that only rate-limited responses cause a wait)
+    pub pace_below: u32,
+    /// The field delimiter for CSV input and the audit file (a comma if not provided)
+    pub csv_delimiter: Option<u8>,
+    /// How long to wait between block requests
+    pub delay: Option<Duration>,
+    /// Apply the filters and report who would be blocked without sending any block requests
+    pub dry_run: bool,
+}
+
+/// Counts of the outcomes of a bulk block operation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockSummary {
+    pub protected: usize,
+    pub following: usize,
+    pub known_blocked: usize,
+    pub newly_blocked: usize,
+    pub already_blocked: usize,
+    pub not_found: usize,
+    pub rate_limited: usize,
+    pub other_success: usize,
+    pub other_non_success: usize,
+    /// The logins that were newly blocked (or that would have been, for a dry run)
+    pub blocked: Vec<String>,
+}
+
+impl BlockSummary {
+    /// Whether any block failed (already blocked accounts are not failures, and missing accounts
+    /// only count if `strict` is set)
+    pub fn has_failures(&self, strict: bool) -> bool {
+        self.other_non_success > 0 || self.rate_limited > 0 || (strict && self.not_found > 0)
+    }
+
+    fn record(&mut self, username: &str, status: BlockStatus) {
+        match status {
+            BlockStatus::NewlyBlocked => {
+                log::info!("Successfully blocked {}", username);
+                self.newly_blocked += 1;
+                self.blocked.push(username.to_string());
+            }
+            BlockStatus::AlreadyBlocked => {
+                log::warn!("{} was already blocked", username);
+                self.already_blocked += 1;
+            }
+            BlockStatus::UserNotFound => {
+                log::warn!("{} was not found", username);
+                self.not_found += 1;
+            }
+            BlockStatus::RateLimited { .. } => {
+                log::error!("Rate limited while blocking {}; giving up", username);
+                self.rate_limited += 1;
+            }
+            BlockStatus::OtherSuccess(status_code) => {
+                log::error!("Unknown success status code: {:?}", status_code);
+                self.other_success += 1;
+            }
+            BlockStatus::OtherNonSuccess(message) => {
+                log::error!("Unknown non-success message: {}", message);
+                self.other_non_success += 1;
+            }
+        }
+    }
+}
+
+/// Read logins that must never be blocked (one per line), lowercasing them for comparison
+pub fn read_protected_logins<R: Read>(mut reader: R) -> std::io::Result<HashSet<String>> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_lowercase)
+        .collect())
+}
+
+/// Block users listed in CSV format for a user or organization
+///
+/// The first column is the login, and the optional second column is a reason (which is only used
+/// for the local audit file, since GitHub doesn't accept one).
+pub async fn block_from_csv<R: Read>(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    reader: R,
+    options: &BlockOptions,
+) -> error::Result<BlockSummary> {
+    block_from_reader(instance, organization, reader, InputFormat::Csv, options).await
+}
+
+/// Block users listed in the given format for a user or organization (see `block_from_csv`)
+pub async fn block_from_reader<R: Read>(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    reader: R,
+    format: InputFormat,
+    options: &BlockOptions,
+) -> error::Result<BlockSummary> {
+    let delimiter = options.csv_delimiter.unwrap_or(DEFAULT_CSV_DELIMITER);
+    let usernames = read_usernames_in_format(reader, format, delimiter)?;
+
+    block_usernames(instance, organization, usernames, options).await
+}
+
+/// Block users (each with an optional reason for the audit file) for a user or organization
+///
+/// The same filters are applied as for `block_from_reader`.
+pub async fn block_usernames(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    mut usernames: Vec<(String, Option<String>)>,
+    options: &BlockOptions,
+) -> error::Result<BlockSummary> {
+    let delimiter = options.csv_delimiter.unwrap_or(DEFAULT_CSV_DELIMITER);
+    let mut summary = BlockSummary::default();
+
+    let mut audit_writer = match &options.audit_file {
+        Some(path) => Some(
+            csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(OpenOptions::new().create(true).append(true).open(path)?),
+        ),
+        None => None,
+    };
+
+    if !options.protected.is_empty() {
+        let unfiltered_size = usernames.len();
+
+        usernames.retain(|(username, _)| !options.protected.contains(&username.to_lowercase()));
+        summary.protected = unfiltered_size - usernames.len();
+
+        log::warn!("Skipping {} protected users", summary.protected);
+    }
+
+    if options.exclude_following {
+        let following: HashSet<String> = get_user_list(
+            instance,
+            "user/following",
+            PagingOptions {
+                retry: options.retry,
+                ..PagingOptions::new(FOLLOWING_PAGE_SIZE)
+            },
+        )
+        .and_then(|user| future::ok(user.login.to_lowercase()))
+        .try_collect()
+        .await?;
+
+        let unfiltered_size = usernames.len();
+
+        usernames.retain(|(username, _)| !following.contains(&username.to_lowercase()));
+        summary.following = unfiltered_size - usernames.len();
+
+        log::warn!("Skipping {} followed users", summary.following);
+    }
+
+    if !options.skip_known_check {
+        let known: HashSet<String> = get_blocks_with_options(
+            instance,
+            organization,
+            PagingOptions {
+                retry: options.retry,
+                ..PagingOptions::new(BLOCKS_PAGE_SIZE)
+            },
+        )
+        .and_then(|user| future::ok(user.login.to_lowercase()))
+        .try_collect()
+        .await?;
+
+        let unfiltered_size = usernames.len();
+
+        usernames.retain(|(username, _)| !known.contains(&username.to_lowercase()));
+        summary.known_blocked = unfiltered_size - usernames.len();
+
+        log::warn!("Skipping {} known blocked users", summary.known_blocked);
+    }
+
+    if options.dry_run {
+        for (username, _) in usernames {
+            log::info!("Would block {}", username);
+            summary.blocked.push(username);
+        }
+
+        return Ok(summary);
+    }
+
+    for (i, (username, reason)) in usernames.into_iter().enumerate() {
+        if let Some(delay) = options.delay.filter(|_| i > 0) {
+            tokio::time::sleep(delay).await;
+        }
+
+        let status = block_user_with_retries(instance, organization, &username, options).await?;
+
+        if let (Some(writer), BlockStatus::NewlyBlocked) = (audit_writer.as_mut(), &status) {
+            writer.write_record([
+                username.as_str(),
+                reason.as_deref().unwrap_or_default(),
+                &Utc::now().to_rfc3339(),
+            ])?;
+            // Flush after every record so that an interrupted run still leaves a complete log
+            writer.flush()?;
+        }
+
+        summary.record(&username, status);
+    }
+
+    Ok(summary)
+}
+
+/// Block a user, waiting and trying again if rate limited
+async fn block_user_with_retries(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    username: &str,
+    options: &BlockOptions,
+) -> error::Result<BlockStatus> {
+    let mut attempts = 0;
+
+    loop {
+        let description = format!("block request for {}", username);
+        let (status, status_and_headers) = with_timeout(
+            options.retry.timeout,
+            &description,
+            block_user_with_headers(instance, organization, username),
+        )
+        .await?;
+
+        match status {
+            BlockStatus::RateLimited { retry_after } if attempts < options.retry.retries => {
+                let delay = retry_after.unwrap_or_else(|| options.retry.delay(attempts));
+                log::warn!(
+                    "Rate limited while blocking {}; waiting {} seconds",
+                    username,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                attempts += 1;
+            }
+            other => {
+                pace(options.pace_below, &status_and_headers).await;
+                return Ok(other);
+            }
+        }
+    }
+}
+
+/// Sleep until the rate limit resets if fewer than `threshold` requests remain in the window
+async fn pace(threshold: u32, status_and_headers: &StatusAndHeaders) {
+    if let (Some(remaining), Some(delay)) = (
+        status_and_headers.rate_limit_remaining(),
+        status_and_headers.until_rate_limit_reset(),
+    ) {
+        if remaining < threshold {
+            log::warn!(
+                "Only {} requests remaining; waiting {} seconds for the rate limit to reset",
+                remaining,
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
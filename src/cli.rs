@@ -1,6 +1,6 @@
 use simplelog::LevelFilter;
 
-fn select_log_level_filter(verbosity: i32) -> LevelFilter {
+fn select_log_level_filter(verbosity: u8) -> LevelFilter {
     match verbosity {
         0 => LevelFilter::Off,
         1 => LevelFilter::Error,
@@ -11,11 +11,42 @@ fn select_log_level_filter(verbosity: i32) -> LevelFilter {
     }
 }
 
-pub fn init_logging(verbosity: i32) -> Result<(), log::SetLoggerError> {
+/// The log level for a verbosity (if `quiet` is set, at most errors are logged)
+fn log_level_filter(verbosity: u8, quiet: bool) -> LevelFilter {
+    let level_filter = select_log_level_filter(verbosity);
+
+    if quiet {
+        level_filter.min(LevelFilter::Error)
+    } else {
+        level_filter
+    }
+}
+
+/// Initialize logging to stderr (if `quiet` is set, at most errors are logged, regardless of
+/// verbosity)
+pub fn init_logging(verbosity: u8, quiet: bool) -> Result<(), log::SetLoggerError> {
+    let level_filter = log_level_filter(verbosity, quiet);
+
     simplelog::TermLogger::init(
-        select_log_level_filter(verbosity),
+        level_filter,
         simplelog::Config::default(),
         simplelog::TerminalMode::Stderr,
         simplelog::ColorChoice::Auto,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_never_raises_log_level() {
+        for verbosity in 0..=5 {
+            assert!(log_level_filter(verbosity, true) <= select_log_level_filter(verbosity));
+            assert!(log_level_filter(verbosity, true) <= LevelFilter::Error);
+        }
+
+        assert_eq!(log_level_filter(0, true), LevelFilter::Off);
+        assert_eq!(log_level_filter(4, true), LevelFilter::Error);
+    }
+}
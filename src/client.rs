@@ -0,0 +1,290 @@
+use super::{
+    block, check_follow, error, get_blocks_with_options, get_user, get_user_list,
+    get_user_list_pages, get_users_info_chunked_with_retries, models, pull_requests_with_options,
+    BlockOptions, BlockStatus, BlockSummary, PagingOptions, RetryConfig,
+};
+use futures::stream::{LocalBoxStream, Stream};
+use octocrab::{
+    models::{pulls::PullRequest, Repository, StarGazer, User},
+    Octocrab, Page,
+};
+use std::io::Read;
+use std::time::Duration;
+
+const DEFAULT_PAGE_SIZE: u8 = 100;
+const DEFAULT_PREFETCH_PAGES: usize = 0;
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_GRAPHQL_CHUNK_SIZE: usize = 50;
+
+/// Configuration shared by all operations performed through an `OctocrabbyClient`
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The number of items to request per page for paginated endpoints
+    pub page_size: u8,
+    /// The number of pages to fetch ahead for paginated endpoints (zero disables prefetching)
+    pub prefetch_pages: usize,
+    /// The maximum number of requests to have in flight at once
+    pub concurrency: usize,
+    /// The number of users to request in each GraphQL query
+    pub graphql_chunk_size: usize,
+    /// How many times to retry failed page requests, GraphQL chunks, and rate-limited blocks
+    pub retry: RetryConfig,
+    /// How long to wait for each REST request (paging and blocking)
+    pub rest_timeout: Option<Duration>,
+    /// How long to wait for each GraphQL query (which can be much slower than REST requests)
+    pub graphql_timeout: Option<Duration>,
+    /// The API base URL (GitHub's public API if not provided)
+    pub base_url: Option<String>,
+    /// Log and skip GraphQL chunks that fail after retrying instead of failing the operation
+    pub continue_on_error: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            page_size: DEFAULT_PAGE_SIZE,
+            prefetch_pages: DEFAULT_PREFETCH_PAGES,
+            concurrency: DEFAULT_CONCURRENCY,
+            graphql_chunk_size: DEFAULT_GRAPHQL_CHUNK_SIZE,
+            retry: RetryConfig::default(),
+            rest_timeout: None,
+            graphql_timeout: None,
+            base_url: None,
+            continue_on_error: false,
+        }
+    }
+}
+
+/// An Octocrab instance together with the configuration used for all of its operations
+pub struct OctocrabbyClient {
+    instance: Octocrab,
+    config: Config,
+}
+
+impl OctocrabbyClient {
+    /// Initialize a client with an optional personal access token
+    pub fn new(token: Option<String>, config: Config) -> octocrab::Result<Self> {
+        let instance = super::init_with_base_url(token, config.base_url.as_deref())?;
+
+        Ok(Self::from_instance(instance, config))
+    }
+
+    /// Wrap an existing Octocrab instance (note that the base URL configuration is ignored)
+    pub fn from_instance(instance: Octocrab, config: Config) -> Self {
+        OctocrabbyClient { instance, config }
+    }
+
+    pub fn instance(&self) -> &Octocrab {
+        &self.instance
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The retry configuration for REST requests (including the REST timeout)
+    pub fn rest_retry(&self) -> RetryConfig {
+        RetryConfig {
+            timeout: self.config.rest_timeout,
+            ..self.config.retry
+        }
+    }
+
+    fn paging(&self) -> PagingOptions {
+        PagingOptions {
+            page_size: self.config.page_size,
+            retry: self.rest_retry(),
+            prefetch: self.config.prefetch_pages,
+        }
+    }
+
+    /// The retry configuration for GraphQL queries (including the GraphQL timeout)
+    pub fn graphql_retry(&self) -> RetryConfig {
+        RetryConfig {
+            timeout: self.config.graphql_timeout,
+            ..self.config.retry
+        }
+    }
+
+    pub fn followers(&self) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+        get_user_list(&self.instance, "user/followers", self.paging())
+    }
+
+    /// Stream the followers of any user
+    pub fn followers_of<'a>(
+        &'a self,
+        username: &'a str,
+    ) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+        super::get_followers_for_with_options(&self.instance, username, self.paging())
+    }
+
+    pub fn following(&self) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+        get_user_list(&self.instance, "user/following", self.paging())
+    }
+
+    /// Stream the followers of any user with account details (via GraphQL)
+    pub fn followers_info<'a>(
+        &'a self,
+        username: &'a str,
+    ) -> impl Stream<Item = error::Result<models::UserInfo>> + 'a {
+        super::get_followers_graphql_with_retries(&self.instance, username, self.graphql_retry())
+    }
+
+    /// Stream pages of the authenticated user's followers, optionally resuming from a page URL
+    pub fn followers_pages<'a>(
+        &'a self,
+        resume_from: Option<&'a str>,
+    ) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+        get_user_list_pages(&self.instance, "user/followers", resume_from, self.paging())
+    }
+
+    /// Stream pages of accounts the authenticated user follows, optionally resuming from a page URL
+    pub fn following_pages<'a>(
+        &'a self,
+        resume_from: Option<&'a str>,
+    ) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+        get_user_list_pages(&self.instance, "user/following", resume_from, self.paging())
+    }
+
+    /// Stream pages of blocked accounts, optionally resuming from a page URL
+    pub fn blocks_pages<'a>(
+        &'a self,
+        organization: Option<&'a str>,
+        resume_from: Option<&'a str>,
+    ) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+        super::get_blocks_pages_with_options(
+            &self.instance,
+            organization,
+            resume_from,
+            self.paging(),
+        )
+    }
+
+    pub fn stargazers<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+    ) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+        super::get_stargazers_with_options(&self.instance, owner, repo, self.paging())
+    }
+
+    pub fn stargazers_with_timestamps<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+    ) -> impl Stream<Item = octocrab::Result<StarGazer>> + 'a {
+        super::get_stargazers_with_timestamps_with_options(
+            &self.instance,
+            owner,
+            repo,
+            self.paging(),
+        )
+    }
+
+    pub fn org_repos<'a>(
+        &'a self,
+        organization: &'a str,
+    ) -> impl Stream<Item = octocrab::Result<Repository>> + 'a {
+        super::get_org_repos_with_options(&self.instance, organization, self.paging())
+    }
+
+    pub fn blocks<'a>(
+        &'a self,
+        organization: Option<&'a str>,
+    ) -> LocalBoxStream<'a, error::Result<User>> {
+        get_blocks_with_options(&self.instance, organization, self.paging())
+    }
+
+    pub fn pull_requests<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+        state: octocrab::params::State,
+    ) -> impl Stream<Item = octocrab::Result<PullRequest>> + 'a {
+        self.pull_requests_with_base(owner, repo, state, None)
+    }
+
+    /// Stream pull requests, optionally only those targeting the given base branch
+    pub fn pull_requests_with_base<'a>(
+        &'a self,
+        owner: &'a str,
+        repo: &'a str,
+        state: octocrab::params::State,
+        base: Option<&'a str>,
+    ) -> impl Stream<Item = octocrab::Result<PullRequest>> + 'a {
+        pull_requests_with_options(&self.instance, owner, repo, state, base, self.paging())
+    }
+
+    pub fn users_info<'a>(
+        &'a self,
+        usernames: &'a [&'a str],
+    ) -> impl Stream<Item = octocrab::Result<models::UserInfo>> + 'a {
+        get_users_info_chunked_with_retries(
+            &self.instance,
+            usernames,
+            self.config.graphql_chunk_size,
+            self.graphql_retry(),
+            self.config.continue_on_error,
+        )
+    }
+
+    pub async fn user(&self, username: &str) -> octocrab::Result<models::ExtendedUser> {
+        get_user(&self.instance, username).await
+    }
+
+    pub async fn current_user(&self) -> octocrab::Result<models::ExtendedUser> {
+        super::get_current_user(&self.instance).await
+    }
+
+    pub async fn org_membership(&self, organization: &str) -> error::Result<models::OrgMembership> {
+        super::check_org_membership(&self.instance, organization).await
+    }
+
+    pub async fn check_follow(&self, source: &str, target: &str) -> octocrab::Result<bool> {
+        check_follow(&self.instance, source, target).await
+    }
+
+    pub async fn block(
+        &self,
+        organization: Option<&str>,
+        username: &str,
+    ) -> error::Result<BlockStatus> {
+        super::block_user(&self.instance, organization, username).await
+    }
+
+    pub async fn block_from_csv<R: Read>(
+        &self,
+        organization: Option<&str>,
+        reader: R,
+        skip_known_check: bool,
+    ) -> error::Result<BlockSummary> {
+        let options = BlockOptions {
+            skip_known_check,
+            retry: self.rest_retry(),
+            ..BlockOptions::default()
+        };
+
+        block::block_from_csv(&self.instance, organization, reader, &options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paging_uses_rest_timeout() {
+        let config = Config {
+            rest_timeout: Some(Duration::from_secs(10)),
+            graphql_timeout: Some(Duration::from_secs(60)),
+            ..Config::default()
+        };
+        let client = OctocrabbyClient::from_instance(Octocrab::default(), config);
+
+        assert_eq!(client.paging().retry.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(
+            client.graphql_retry().timeout,
+            Some(Duration::from_secs(60))
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors that are specific to this crate (or that wrap Octocrab's errors with more context)
+#[derive(Debug)]
+pub enum Error {
+    Octocrab(octocrab::Error),
+    Csv(csv::Error),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The organization requires the token to be authorized for SAML single sign-on
+    SsoRequired {
+        organization: String,
+        authorization_url: Option<String>,
+    },
+    /// A resume URL couldn't be parsed or is no longer accepted by GitHub (e.g. a stale cursor)
+    InvalidResumeUrl {
+        url: String,
+        source: Option<Box<octocrab::Error>>,
+    },
+    /// A resume URL doesn't belong to the configured API (so the token isn't sent to it)
+    ForeignResumeUrl {
+        url: String,
+        base_url: String,
+    },
+    /// The token hasn't been granted a scope needed for an operation
+    MissingScope {
+        scope: String,
+        granted: Vec<String>,
+    },
+    /// A request didn't complete within its configured timeout
+    Timeout(Timeout),
+    /// The authenticated user isn't an active member of the organization (or it doesn't exist)
+    NotOrgMember {
+        organization: String,
+    },
+    /// A GraphQL query found no user with the given login
+    UserNotFound {
+        login: String,
+    },
+}
+
+/// A request that didn't complete within its configured timeout
+///
+/// Streams of Octocrab results carry this as the source of an `octocrab::Error::Other`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeout {
+    pub operation: String,
+    pub duration: Duration,
+}
+
+impl Timeout {
+    pub(crate) fn into_octocrab_error(self) -> octocrab::Error {
+        octocrab::Error::Other {
+            source: Box::new(self),
+            backtrace: snafu::GenerateImplicitData::generate(),
+        }
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The {} timed out after {} milliseconds",
+            self.operation,
+            self.duration.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+/// A GraphQL request was rejected in a way that indicates the GraphQL API isn't available
+///
+/// Like `Timeout`, this is carried as the source of an `octocrab::Error::Other`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphQlUnavailable {
+    pub status_code: u16,
+    pub message: String,
+}
+
+impl GraphQlUnavailable {
+    pub(crate) fn into_octocrab_error(self) -> octocrab::Error {
+        octocrab::Error::Other {
+            source: Box::new(self),
+            backtrace: snafu::GenerateImplicitData::generate(),
+        }
+    }
+}
+
+impl fmt::Display for GraphQlUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The GraphQL API is unavailable (status {}: {})",
+            self.status_code, self.message
+        )
+    }
+}
+
+impl std::error::Error for GraphQlUnavailable {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Octocrab(error) => error.fmt(f),
+            Error::Csv(error) => error.fmt(f),
+            Error::Io(error) => error.fmt(f),
+            Error::Json(error) => error.fmt(f),
+            Error::SsoRequired {
+                organization,
+                authorization_url,
+            } => {
+                write!(
+                    f,
+                    "The organization {} requires SAML SSO authorization for this token",
+                    organization
+                )?;
+
+                match authorization_url {
+                    Some(url) => write!(f, "; authorize it here: {}", url),
+                    None => write!(f, "; authorize it in your token settings"),
+                }
+            }
+            Error::InvalidResumeUrl { url, source } => {
+                write!(f, "Invalid or expired resume URL: {}", url)?;
+
+                match source {
+                    Some(error) => write!(f, " ({})", error),
+                    None => Ok(()),
+                }
+            }
+            Error::ForeignResumeUrl { url, base_url } => write!(
+                f,
+                "The resume URL {} doesn't belong to the API at {}",
+                url, base_url
+            ),
+            Error::MissingScope { scope, granted } => write!(
+                f,
+                "The token is missing the {} scope (granted: {}); update it in your token settings",
+                scope,
+                if granted.is_empty() {
+                    "none".to_string()
+                } else {
+                    granted.join(", ")
+                }
+            ),
+            Error::Timeout(timeout) => timeout.fmt(f),
+            Error::NotOrgMember { organization } => write!(
+                f,
+                "The authenticated user is not an active member of the organization {} \
+                 (check that the name is correct)",
+                organization
+            ),
+            Error::UserNotFound { login } => write!(f, "No user found with the login {}", login),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Octocrab(error) => Some(error),
+            Error::Csv(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::Json(error) => Some(error),
+            Error::SsoRequired { .. }
+            | Error::ForeignResumeUrl { .. }
+            | Error::MissingScope { .. }
+            | Error::Timeout(_)
+            | Error::NotOrgMember { .. }
+            | Error::UserNotFound { .. } => None,
+            Error::InvalidResumeUrl { source, .. } => source
+                .as_ref()
+                .map(|error| error.as_ref() as &(dyn std::error::Error + 'static)),
+        }
+    }
+}
+
+impl From<octocrab::Error> for Error {
+    fn from(error: octocrab::Error) -> Self {
+        match error {
+            octocrab::Error::Other { source, backtrace } => match source.downcast::<Timeout>() {
+                Ok(timeout) => Error::Timeout(*timeout),
+                Err(source) => Error::Octocrab(octocrab::Error::Other { source, backtrace }),
+            },
+            other => Error::Octocrab(other),
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Self {
+        Error::Csv(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,29 +1,87 @@
+pub mod block;
 pub mod cli;
+pub mod client;
+pub mod error;
 pub mod models;
+pub mod output;
+pub mod scopes;
 
+use chrono::{DateTime, TimeZone, Utc};
+use error::Error;
 use futures::stream::{self, LocalBoxStream, Stream, StreamExt, TryStreamExt};
-use futures::{future, Future, FutureExt};
+use futures::{future, Future, FutureExt, SinkExt};
 use itertools::Itertools;
 use octocrab::{
-    models::{pulls::PullRequest, User},
-    Octocrab, Page,
+    models::{pulls::PullRequest, Repository, StarGazer, User},
+    FromResponse, Octocrab, Page,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT, RETRY_AFTER},
+    Response, StatusCode,
 };
-use reqwest::{Response, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize};
 use std::collections::{HashMap, HashSet};
-use std::io::Read;
+use std::io::{Read, Write};
 use std::pin::Pin;
+use std::time::Duration;
+use url::Url;
 
 const PULL_REQUESTS_PAGE_SIZE: u8 = 100;
 const FOLLOWERS_PAGE_SIZE: u8 = 100;
 const FOLLOWING_PAGE_SIZE: u8 = 100;
 const BLOCKS_PAGE_SIZE: u8 = 100;
+const REPOS_PAGE_SIZE: u8 = 100;
+const STARGAZERS_PAGE_SIZE: u8 = 100;
+const STAR_MEDIA_TYPE: &str = "application/vnd.github.v3.star+json";
 const BLOCK_304_MESSAGE: &str = "Blocked user has already been blocked";
 const BLOCK_404_MESSAGE: &str = "Not Found";
+const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
+/// The environment variable GitHub Actions uses for the REST API base URL
+pub const GITHUB_API_URL_VAR: &str = "GITHUB_API_URL";
+const SSO_HEADER: &str = "x-github-sso";
+const OAUTH_SCOPES_HEADER: &str = "x-oauth-scopes";
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_secs(5);
+const RETRY_MAX_DOUBLINGS: u32 = 16;
+const RATE_LIMIT_MESSAGES: [&str; 2] = ["rate limit", "abuse detection"];
+/// The field delimiter for CSV input and output unless otherwise configured
+pub const DEFAULT_CSV_DELIMITER: u8 = b',';
+/// Responses from the GraphQL endpoint that indicate that it isn't available at all (as opposed to
+/// errors for a particular query, which GitHub reports with more specific messages)
+const GRAPHQL_UNAVAILABLE_RESPONSES: [(StatusCode, &str); 2] = [
+    (StatusCode::NOT_FOUND, "Not Found"),
+    (StatusCode::FORBIDDEN, "Forbidden"),
+];
+
+pub use block::{block_from_csv, block_from_reader, block_usernames, BlockOptions, BlockSummary};
+pub use client::{Config, OctocrabbyClient};
 
 /// Initialize a client instance with defaults and configuration
 pub fn init(token: Option<String>) -> octocrab::Result<Octocrab> {
-    let builder = octocrab::OctocrabBuilder::new();
+    init_with_base_url(token, None)
+}
+
+/// Initialize a client instance for a specific API base URL (e.g. for GitHub Enterprise)
+///
+/// If no base URL is provided, the `GITHUB_API_URL` environment variable (as set by GitHub Actions)
+/// is used if present.
+pub fn init_with_base_url(
+    token: Option<String>,
+    base_url: Option<&str>,
+) -> octocrab::Result<Octocrab> {
+    build_instance(
+        token,
+        resolve_base_url(base_url, std::env::var(GITHUB_API_URL_VAR).ok()),
+    )
+}
+
+fn build_instance(token: Option<String>, base_url: Option<String>) -> octocrab::Result<Octocrab> {
+    let mut builder = octocrab::OctocrabBuilder::new();
+
+    if let Some(value) = base_url {
+        builder = builder.base_url(value)?;
+    }
 
     match token {
         Some(value) => builder.personal_token(value).build(),
@@ -31,6 +89,68 @@ pub fn init(token: Option<String>) -> octocrab::Result<Octocrab> {
     }
 }
 
+/// Choose between an explicit base URL and the environment's, adding the trailing slash that URL
+/// joining needs (`GITHUB_API_URL` doesn't include one)
+fn resolve_base_url(explicit: Option<&str>, from_env: Option<String>) -> Option<String> {
+    let mut value = explicit
+        .map(str::to_string)
+        .or_else(|| from_env.filter(|value| !value.trim().is_empty()))?;
+
+    if !value.ends_with('/') {
+        value.push('/');
+    }
+
+    Some(value)
+}
+
+/// The GraphQL endpoint for an instance
+///
+/// GitHub Enterprise serves GraphQL from `/api/graphql`, not under the REST base of `/api/v3/`.
+fn graphql_url(instance: &Octocrab) -> octocrab::Result<Url> {
+    let base = instance.absolute_url("")?;
+
+    if base.path().ends_with("/api/v3/") {
+        instance.absolute_url("../graphql")
+    } else {
+        instance.absolute_url("graphql")
+    }
+}
+
+/// Run a GraphQL query against the instance's GraphQL endpoint
+///
+/// Responses indicating that GraphQL isn't available are returned as `GraphQlUnavailable` errors.
+async fn graphql<R: FromResponse>(instance: &Octocrab, query: &str) -> octocrab::Result<R> {
+    let response = instance
+        ._post(
+            graphql_url(instance)?,
+            Some(&serde_json::json!({ "query": query })),
+        )
+        .await?;
+    let status_code = response.status();
+
+    match octocrab::map_github_error(response).await {
+        Ok(response) => R::from_response(response).await,
+        Err(octocrab::Error::GitHub { source, .. })
+            if is_graphql_unavailable_response(status_code, &source.message) =>
+        {
+            Err(error::GraphQlUnavailable {
+                status_code: status_code.as_u16(),
+                message: source.message,
+            }
+            .into_octocrab_error())
+        }
+        Err(other) => Err(other),
+    }
+}
+
+fn is_graphql_unavailable_response(status_code: StatusCode, message: &str) -> bool {
+    GRAPHQL_UNAVAILABLE_RESPONSES
+        .iter()
+        .any(|(expected_status_code, expected_message)| {
+            status_code == *expected_status_code && message == *expected_message
+        })
+}
+
 /// Parse a repo "path" (e.g. "travisbrown/octocrabby")
 pub fn parse_repo_path(path: &str) -> Option<(&str, &str)> {
     let parts = path.split('/').collect::<Vec<_>>();
@@ -42,21 +162,474 @@ pub fn parse_repo_path(path: &str) -> Option<(&str, &str)> {
     }
 }
 
+/// Parse a pull request "path" (e.g. "travisbrown/octocrabby#1" or "travisbrown/octocrabby/1")
+pub fn parse_pull_request_path(path: &str) -> Option<(&str, &str, u64)> {
+    let (repo_path, number) = match path.split_once('#') {
+        Some(parts) => parts,
+        None => path.rsplit_once('/')?,
+    };
+    let (owner, repo) = parse_repo_path(repo_path)?;
+
+    Some((owner, repo, number.parse().ok()?))
+}
+
+/// Read GitHub login usernames from CSV input
+///
+/// Note that only the first field is used, and is expected to be a GitHub login username.
+pub fn read_usernames<R: Read>(reader: R) -> csv::Result<Vec<String>> {
+    Ok(read_usernames_with_reasons(reader)?
+        .into_iter()
+        .map(|(username, _)| username)
+        .collect())
+}
+
+/// Read GitHub login usernames from CSV input, along with an optional reason in the second field
+///
+/// Rows may have any number of fields, and an empty reason is treated as missing.
+pub fn read_usernames_with_reasons<R: Read>(
+    reader: R,
+) -> csv::Result<Vec<(String, Option<String>)>> {
+    read_delimited_usernames(reader, DEFAULT_CSV_DELIMITER)
+}
+
+/// Read GitHub login usernames and optional reasons from CSV input with the given field delimiter
+pub fn read_delimited_usernames<R: Read>(
+    reader: R,
+    delimiter: u8,
+) -> csv::Result<Vec<(String, Option<String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(reader);
+    let mut usernames = vec![];
+
+    for record in reader.records() {
+        let record = record?;
+        let reason = record
+            .get(1)
+            .map(str::trim)
+            .filter(|reason| !reason.is_empty())
+            .map(str::to_string);
+
+        usernames.push((record.get(0).unwrap().to_string(), reason));
+    }
+
+    Ok(usernames)
+}
+
+/// Formats for lists of logins
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InputFormat {
+    /// Login in the first field and an optional reason in the second (no header row)
+    #[default]
+    Csv,
+    /// A JSON array of objects with a `login` field and an optional `reason` field
+    Json,
+    /// One JSON object (as for `Json`) per line
+    Jsonl,
+}
+
+#[derive(Deserialize)]
+struct LoginRecord {
+    login: String,
+    reason: Option<String>,
+}
+
+/// Read GitHub login usernames (and optional reasons) in the given format
+///
+/// The delimiter is only used for CSV input.
+pub fn read_usernames_in_format<R: Read>(
+    reader: R,
+    format: InputFormat,
+    delimiter: u8,
+) -> error::Result<Vec<(String, Option<String>)>> {
+    let records: Vec<LoginRecord> = match format {
+        InputFormat::Csv => return Ok(read_delimited_usernames(reader, delimiter)?),
+        InputFormat::Json => serde_json::from_reader(reader)?,
+        InputFormat::Jsonl => serde_json::Deserializer::from_reader(reader)
+            .into_iter()
+            .collect::<Result<_, _>>()?,
+    };
+
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            let reason = record.reason.filter(|reason| !reason.trim().is_empty());
+            (record.login, reason)
+        })
+        .collect())
+}
+
+/// How many times to retry failed requests, how long to wait before the first retry, and how long
+/// to wait for each attempt
+///
+/// The delay doubles after each attempt, and zero retries means that requests are only tried once.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryConfig {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub timeout: Option<Duration>,
+}
+
+impl RetryConfig {
+    pub const NONE: RetryConfig = RetryConfig {
+        retries: 0,
+        base_delay: Duration::from_secs(0),
+        timeout: None,
+    };
+
+    /// The delay before the given retry (counting from zero)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.min(RETRY_MAX_DOUBLINGS)))
+    }
+
+    /// Run an operation, retrying it with exponential backoff if it fails or times out
+    ///
+    /// The description is used to identify the operation in timeout errors.
+    pub async fn run<T, E, F, Fut>(self, description: &str, mut operation: F) -> Result<T, E>
+    where
+        E: From<octocrab::Error>,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        tryhard::retry_fn(|| with_timeout(self.timeout, description, operation()))
+            .retries(self.retries)
+            .exponential_backoff(self.base_delay)
+            .await
+    }
+
+    /// Run an operation like `run`, but give up immediately after errors that retrying can't fix
+    pub async fn run_unless<T, E, F, Fut, P>(
+        self,
+        description: &str,
+        mut operation: F,
+        is_permanent: P,
+    ) -> Result<T, E>
+    where
+        E: From<octocrab::Error> + 'static,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        P: Fn(&E) -> bool,
+    {
+        let mut attempt = 0;
+
+        tryhard::retry_fn(|| with_timeout(self.timeout, description, operation()))
+            .retries(self.retries)
+            .custom_backoff(|_, error: &E| {
+                if is_permanent(error) {
+                    tryhard::RetryPolicy::Break
+                } else {
+                    let delay = self.delay(attempt);
+                    attempt += 1;
+                    tryhard::RetryPolicy::Delay(delay)
+                }
+            })
+            .await
+    }
+}
+
+/// Fail with a `Timeout` error (wrapped in Octocrab's error type) if a request takes too long
+pub(crate) async fn with_timeout<T, E, Fut>(
+    timeout: Option<Duration>,
+    description: &str,
+    future: Fut,
+) -> Result<T, E>
+where
+    E: From<octocrab::Error>,
+    Fut: Future<Output = Result<T, E>>,
+{
+    match timeout {
+        Some(duration) => match tokio::time::timeout(duration, future).await {
+            Ok(result) => result,
+            Err(_) => Err(error::Timeout {
+                operation: description.to_string(),
+                duration,
+            }
+            .into_octocrab_error()
+            .into()),
+        },
+        None => future.await,
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            retries: DEFAULT_RETRIES,
+            base_delay: DEFAULT_RETRY_BASE_DELAY,
+            timeout: None,
+        }
+    }
+}
+
+/// Asynchronously stream pages for a starting page (including the starting page)
+pub fn page_stream<'a, R: DeserializeOwned + 'a>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+) -> impl Stream<Item = octocrab::Result<Page<R>>> + 'a {
+    page_stream_with_headers(instance, start, None)
+}
+
+/// Asynchronously stream pages for a starting page, sending the given headers with each request
+///
+/// This is necessary when a custom media type is needed, since `Octocrab::get_page` doesn't
+/// support custom headers.
+pub fn page_stream_with_headers<'a, R: DeserializeOwned + 'a>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+    headers: Option<HeaderMap>,
+) -> impl Stream<Item = octocrab::Result<Page<R>>> + 'a {
+    page_stream_with_retries(instance, start, headers, RetryConfig::NONE)
+}
+
+pub(crate) fn page_stream_with_retries<'a, R: DeserializeOwned + 'a>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+    headers: Option<HeaderMap>,
+    retry: RetryConfig,
+) -> impl Stream<Item = octocrab::Result<Page<R>>> + 'a {
+    stream::try_unfold(Some(start), move |current| {
+        let headers = headers.clone();
+
+        async move {
+            match current {
+                Some(current_page) => {
+                    let next = match &current_page.next {
+                        Some(url) => Some(
+                            retry
+                                .run(&format!("page request for {}", url), || {
+                                    instance.get_with_headers::<Page<R>, _, ()>(
+                                        url,
+                                        None,
+                                        headers.clone(),
+                                    )
+                                })
+                                .await?,
+                        ),
+                        None => None,
+                    };
+
+                    Ok(Some((current_page, next)))
+                }
+                None => Ok(None),
+            }
+        }
+    })
+}
+
 /// Asynchronously stream results for a starting page
 pub fn pager_stream<'a, R: DeserializeOwned + 'a>(
     instance: &'a Octocrab,
     start: Page<R>,
 ) -> impl Stream<Item = octocrab::Result<R>> + 'a {
-    stream::try_unfold(Some(start), move |current| async move {
-        match current {
-            Some(current_page) => instance
-                .get_page::<R>(&current_page.next)
-                .await
-                .map(|next| Some((current_page, next))),
-            None => Ok(None),
+    pager_stream_with_retries(instance, start, RetryConfig::NONE)
+}
+
+pub(crate) fn pager_stream_with_retries<'a, R: DeserializeOwned + 'a>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+    retry: RetryConfig,
+) -> impl Stream<Item = octocrab::Result<R>> + 'a {
+    page_stream_with_retries(instance, start, None, retry)
+        .and_then(|mut page| future::ok(stream::iter(page.take_items()).map(Ok)))
+        .try_flatten()
+}
+
+/// Asynchronously stream results for a starting page, fetching up to `depth` pages ahead
+///
+/// Each page still has to wait for the previous page's `next` link, but the following pages are
+/// requested in a background task while the current page's items are consumed. The results are
+/// returned in order, and the stream ends after the final page (or the first error). This must be
+/// called from within a Tokio runtime.
+pub fn prefetching_pager_stream<R: DeserializeOwned + Send + 'static>(
+    instance: &Octocrab,
+    start: Page<R>,
+    depth: usize,
+) -> impl Stream<Item = octocrab::Result<R>> + 'static {
+    prefetching_pager_stream_with_retries(instance, start, depth, RetryConfig::NONE)
+}
+
+fn prefetching_pager_stream_with_retries<R: DeserializeOwned + Send + 'static>(
+    instance: &Octocrab,
+    start: Page<R>,
+    depth: usize,
+    retry: RetryConfig,
+) -> impl Stream<Item = octocrab::Result<R>> + 'static {
+    prefetching_page_stream_with_retries(instance, start, None, depth, retry)
+        .and_then(|mut page: Page<R>| future::ok(stream::iter(page.take_items()).map(Ok)))
+        .try_flatten()
+}
+
+/// Stream pages for a starting page, fetching up to `depth` pages ahead in a background task
+fn prefetching_page_stream_with_retries<R: DeserializeOwned + Send + 'static>(
+    instance: &Octocrab,
+    start: Page<R>,
+    headers: Option<HeaderMap>,
+    depth: usize,
+    retry: RetryConfig,
+) -> impl Stream<Item = octocrab::Result<Page<R>>> + 'static {
+    let instance = instance.clone();
+    // The channel's capacity is one more than its buffer size (since there is a single sender)
+    let (mut sender, receiver) = futures::channel::mpsc::channel(depth.saturating_sub(1));
+
+    tokio::spawn(async move {
+        let pages = page_stream_with_retries(&instance, start, headers, retry);
+        futures::pin_mut!(pages);
+
+        while let Some(page) = pages.next().await {
+            let is_error = page.is_err();
+
+            // Stop if the receiver has been dropped or there are no more pages after an error
+            if sender.send(page).await.is_err() || is_error {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// How to request a paginated endpoint
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PagingOptions {
+    pub(crate) page_size: u8,
+    pub(crate) retry: RetryConfig,
+    /// The number of pages to fetch ahead (zero means pages are only fetched as needed)
+    pub(crate) prefetch: usize,
+}
+
+impl PagingOptions {
+    pub(crate) fn new(page_size: u8) -> Self {
+        PagingOptions {
+            page_size,
+            retry: RetryConfig::NONE,
+            prefetch: 0,
+        }
+    }
+}
+
+pub(crate) fn pager_stream_with_options<'a, R: DeserializeOwned + Send + 'static>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+    paging: PagingOptions,
+) -> LocalBoxStream<'a, octocrab::Result<R>> {
+    if paging.prefetch == 0 {
+        Box::pin(pager_stream_with_retries(instance, start, paging.retry))
+    } else {
+        Box::pin(prefetching_pager_stream_with_retries(
+            instance,
+            start,
+            paging.prefetch,
+            paging.retry,
+        ))
+    }
+}
+
+pub(crate) fn page_stream_with_options<'a, R: DeserializeOwned + Send + 'static>(
+    instance: &'a Octocrab,
+    start: Page<R>,
+    headers: Option<HeaderMap>,
+    paging: PagingOptions,
+) -> LocalBoxStream<'a, octocrab::Result<Page<R>>> {
+    if paging.prefetch == 0 {
+        Box::pin(page_stream_with_retries(
+            instance,
+            start,
+            headers,
+            paging.retry,
+        ))
+    } else {
+        Box::pin(prefetching_page_stream_with_retries(
+            instance,
+            start,
+            headers,
+            paging.prefetch,
+            paging.retry,
+        ))
+    }
+}
+
+/// Get a page of results from a URL previously provided as a resume token
+///
+/// The URL must have the same scheme, host, and port as the instance's base URL, since the token
+/// is sent with the request.
+pub async fn get_resume_page<R: DeserializeOwned>(
+    instance: &Octocrab,
+    url: &str,
+) -> error::Result<Page<R>> {
+    get_resume_page_with_retries(instance, url, RetryConfig::NONE).await
+}
+
+/// Get a page from a resume URL, retrying failed requests (but not invalid or expired URLs)
+async fn get_resume_page_with_retries<R: DeserializeOwned>(
+    instance: &Octocrab,
+    url: &str,
+    retry: RetryConfig,
+) -> error::Result<Page<R>> {
+    let parsed = Url::parse(url).map_err(|_| Error::InvalidResumeUrl {
+        url: url.to_string(),
+        source: None,
+    })?;
+    let base_url = instance.absolute_url("")?;
+
+    if !is_same_origin(&parsed, &base_url) {
+        return Err(Error::ForeignResumeUrl {
+            url: url.to_string(),
+            base_url: base_url.to_string(),
+        });
+    }
+
+    retry
+        .run_unless(
+            url,
+            || async {
+                match instance.get_page::<R>(&Some(parsed.clone())).await {
+                    Ok(Some(page)) => Ok(page),
+                    Ok(None) => unreachable!("A page is always returned for a URL"),
+                    Err(error @ octocrab::Error::GitHub { .. }) => Err(Error::InvalidResumeUrl {
+                        url: url.to_string(),
+                        source: Some(Box::new(error)),
+                    }),
+                    Err(other) => Err(other.into()),
+                }
+            },
+            |error| matches!(error, Error::InvalidResumeUrl { .. }),
+        )
+        .await
+}
+
+fn is_same_origin(url: &Url, base_url: &Url) -> bool {
+    url.scheme() == base_url.scheme()
+        && url.host_str() == base_url.host_str()
+        && url.port_or_known_default() == base_url.port_or_known_default()
+}
+
+/// Stream pages starting from either the given first page or a resume URL
+///
+/// The first page is requested by calling `first` (once for each attempt).
+fn resumable_page_stream<'a, R, F, Fut>(
+    instance: &'a Octocrab,
+    route: String,
+    first: F,
+    resume_from: Option<&'a str>,
+    paging: PagingOptions,
+) -> impl Stream<Item = error::Result<Page<R>>> + 'a
+where
+    R: DeserializeOwned + Send + 'static,
+    F: FnMut() -> Fut + 'a,
+    Fut: Future<Output = error::Result<Page<R>>> + 'a,
+{
+    stream::once(async move {
+        match resume_from {
+            Some(url) => get_resume_page_with_retries(instance, url, paging.retry).await,
+            None => paging.retry.run(&route, first).await,
         }
     })
-    .and_then(|mut page| future::ok(stream::iter(page.take_items()).map(Ok)))
+    .and_then(move |page| {
+        future::ok(page_stream_with_options(instance, page, None, paging).map_err(Error::from))
+    })
     .try_flatten()
 }
 
@@ -65,40 +638,195 @@ pub fn pull_requests<'a>(
     instance: &'a Octocrab,
     owner: &'a str,
     repo: &'a str,
+    state: octocrab::params::State,
+) -> impl Stream<Item = octocrab::Result<PullRequest>> + 'a {
+    pull_requests_with_options(
+        instance,
+        owner,
+        repo,
+        state,
+        None,
+        PagingOptions::new(PULL_REQUESTS_PAGE_SIZE),
+    )
+}
+
+pub(crate) fn pull_requests_with_options<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
+    state: octocrab::params::State,
+    base: Option<&'a str>,
+    paging: PagingOptions,
 ) -> impl Stream<Item = octocrab::Result<PullRequest>> + 'a {
     stream::once(async move {
-        instance
-            .pulls(owner, repo)
-            .list()
-            .state(octocrab::params::State::All)
-            .per_page(PULL_REQUESTS_PAGE_SIZE)
-            .send()
+        paging
+            .retry
+            .run(
+                &format!("pull request listing for {}/{}", owner, repo),
+                || async move {
+                    let pulls = instance.pulls(owner, repo);
+                    let mut builder = pulls.list().state(state).per_page(paging.page_size);
+
+                    // GitHub returns an empty list (not an error) if the branch doesn't exist
+                    if let Some(base) = base {
+                        builder = builder.base(base);
+                    }
+
+                    builder.send().await
+                },
+            )
             .await
     })
-    .and_then(move |page| future::ok(pager_stream(instance, page)))
+    .and_then(move |page| future::ok(pager_stream_with_options(instance, page, paging)))
     .try_flatten()
 }
 
-struct StatusCodeWrapper(StatusCode);
+/// The status code and headers of a response
+///
+/// GitHub reports rate limit and retry information in headers, which are discarded by Octocrab's
+/// `FromResponse` instances for deserializable types.
+#[derive(Clone, Debug)]
+pub struct StatusAndHeaders {
+    pub status_code: StatusCode,
+    pub headers: HeaderMap,
+}
+
+impl StatusAndHeaders {
+    fn new(response: &Response) -> Self {
+        StatusAndHeaders {
+            status_code: response.status(),
+            headers: response.headers().clone(),
+        }
+    }
+
+    fn header_str(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim())
+    }
+
+    /// The delay requested by the `Retry-After` header (only the delay-seconds form is supported)
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.header_str(RETRY_AFTER.as_str())
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_secs)
+    }
+
+    /// The number of requests remaining in the current rate limit window
+    pub fn rate_limit_remaining(&self) -> Option<u32> {
+        self.header_str(RATE_LIMIT_REMAINING_HEADER)
+            .and_then(|value| value.parse::<u32>().ok())
+    }
+
+    /// The time at which the current rate limit window resets
+    pub fn rate_limit_reset(&self) -> Option<DateTime<Utc>> {
+        self.header_str(RATE_LIMIT_RESET_HEADER)
+            .and_then(|value| value.parse::<i64>().ok())
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+    }
+
+    /// Whether the request failed because the token hasn't been authorized for SAML SSO
+    pub fn is_sso_required(&self) -> bool {
+        self.header_str(SSO_HEADER)
+            .is_some_and(|value| value.starts_with("required"))
+    }
+
+    /// The scopes granted to the token, if GitHub reports them (classic tokens only)
+    pub fn oauth_scopes(&self) -> Option<Vec<String>> {
+        Some(
+            self.header_str(OAUTH_SCOPES_HEADER)?
+                .split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// The URL at which the token can be authorized for SAML SSO (e.g. `required; url=...`)
+    pub fn sso_authorization_url(&self) -> Option<&str> {
+        self.header_str(SSO_HEADER)?
+            .split(';')
+            .filter_map(|part| part.trim().strip_prefix("url="))
+            .next()
+    }
+
+    /// Map an error for an organization resource, surfacing SSO authorization failures
+    fn map_org_error(&self, organization: &str, error: octocrab::Error) -> Error {
+        if self.is_sso_required() {
+            Error::SsoRequired {
+                organization: organization.to_string(),
+                authorization_url: self.sso_authorization_url().map(str::to_string),
+            }
+        } else {
+            Error::Octocrab(error)
+        }
+    }
+
+    /// The time remaining until the current rate limit window resets
+    pub fn until_rate_limit_reset(&self) -> Option<Duration> {
+        self.rate_limit_reset()
+            .and_then(|reset| (reset - Utc::now()).to_std().ok())
+    }
+}
 
-impl octocrab::FromResponse for StatusCodeWrapper {
+impl FromResponse for StatusAndHeaders {
     fn from_response<'a>(
         response: Response,
     ) -> Pin<Box<dyn Future<Output = octocrab::Result<Self>> + Send + 'a>> {
-        future::ok(StatusCodeWrapper(response.status())).boxed()
+        future::ok(StatusAndHeaders::new(&response)).boxed()
     }
 }
 
+/// Send a `GET` request, retaining the status code and headers even if GitHub returns an error
+pub async fn get_for_status(
+    instance: &Octocrab,
+    route: &str,
+) -> octocrab::Result<(StatusAndHeaders, octocrab::Result<()>)> {
+    let response = instance
+        ._get(instance.absolute_url(route)?, None::<&()>)
+        .await?;
+    let status_and_headers = StatusAndHeaders::new(&response);
+
+    Ok((
+        status_and_headers,
+        octocrab::map_github_error(response).await.map(|_| ()),
+    ))
+}
+
+/// Send a `PUT` request, retaining the status code and headers even if GitHub returns an error
+pub async fn put_for_status(
+    instance: &Octocrab,
+    route: &str,
+) -> octocrab::Result<(StatusAndHeaders, octocrab::Result<()>)> {
+    let response = instance
+        ._put(instance.absolute_url(route)?, None::<&()>)
+        .await?;
+    let status_and_headers = StatusAndHeaders::new(&response);
+
+    Ok((
+        status_and_headers,
+        octocrab::map_github_error(response).await.map(|_| ()),
+    ))
+}
+
+fn check_follow_route(source: &str, target: &str) -> String {
+    format!("users/{}/following/{}", source, target)
+}
+
 /// Check whether one user follows another
 pub async fn check_follow(
     instance: &Octocrab,
     source: &str,
     target: &str,
 ) -> octocrab::Result<bool> {
-    let route = format!("/users/{}/following/{}", source, target);
+    let route = check_follow_route(source, target);
+
+    let (status_and_headers, result) = get_for_status(instance, &route).await?;
 
-    match instance.get::<StatusCodeWrapper, _, ()>(route, None).await {
-        Ok(StatusCodeWrapper(status_code)) => Ok(status_code == StatusCode::NO_CONTENT),
+    match result {
+        Ok(()) => Ok(status_and_headers.status_code == StatusCode::NO_CONTENT),
         Err(octocrab::Error::GitHub { source, .. }) if source.errors.is_none() => Ok(false),
         Err(other) => Err(other),
     }
@@ -121,11 +849,12 @@ pub async fn get_users_info(
         .join("\n");
 
     let query = format!(
-        "query {{{}}}\nfragment UserFields on User {{ login\ncreatedAt\nname\ntwitterUsername }}",
+        "query {{{}}}\nfragment UserFields on User {{ login\ncreatedAt\nname\ntwitterUsername\n\
+            followers {{ totalCount }}\nfollowing {{ totalCount }} }}",
         user_aliases
     );
 
-    let results: octocrab::Result<GraphQlUserResults> = instance.graphql(&query).await;
+    let results: octocrab::Result<GraphQlUserResults> = graphql(instance, &query).await;
     // TODO: Use `into_values` here when #75294 is out of nightly.
     Ok(results?.data.values().flatten().cloned().collect())
 }
@@ -134,66 +863,314 @@ pub fn get_users_info_chunked<'a>(
     instance: &'a Octocrab,
     usernames: &'a [&'a str],
     chunk_size: usize,
+) -> impl Stream<Item = octocrab::Result<models::UserInfo>> + 'a {
+    get_users_info_chunked_with_retries(instance, usernames, chunk_size, RetryConfig::NONE, false)
+}
+
+/// Load user information in chunks, retrying each failed chunk independently
+///
+/// If `continue_on_error` is set, chunks that still fail after retrying are logged and skipped.
+pub fn get_users_info_chunked_with_retries<'a>(
+    instance: &'a Octocrab,
+    usernames: &'a [&'a str],
+    chunk_size: usize,
+    retry: RetryConfig,
+    continue_on_error: bool,
 ) -> impl Stream<Item = octocrab::Result<models::UserInfo>> + 'a {
     stream::iter(usernames.chunks(chunk_size).map(Ok))
-        .and_then(move |chunk| get_users_info(instance, chunk))
+        .and_then(move |chunk| async move {
+            let result = retry
+                .run("GraphQL user information query", || {
+                    get_users_info(instance, chunk)
+                })
+                .await;
+
+            match result {
+                // Unavailability isn't specific to the chunk, so it's still reported
+                Err(error) if continue_on_error && !is_graphql_unavailable(&error) => {
+                    log::warn!(
+                        "Skipping {} users after a failed GraphQL query: {}",
+                        chunk.len(),
+                        error
+                    );
+                    Ok(vec![])
+                }
+                other => other,
+            }
+        })
         .and_then(|infos| future::ok(stream::iter(infos.into_iter().map(Ok))))
         .try_flatten()
 }
 
-/// Get extended information for a user
-pub async fn get_user(
-    instance: &Octocrab,
-    username: &str,
-) -> octocrab::Result<models::ExtendedUser> {
-    let route = format!("/users/{}", username);
+/// Whether an error indicates that the GraphQL API isn't available (as on some Enterprise setups)
+pub fn is_graphql_unavailable(error: &octocrab::Error) -> bool {
+    match error {
+        octocrab::Error::Other { source, .. } => source.is::<error::GraphQlUnavailable>(),
+        _ => false,
+    }
+}
 
-    instance
-        .get::<models::ExtendedUser, _, ()>(route, None)
-        .await
+/// Load extended profiles via individual REST requests (accounts that can't be found are skipped)
+pub fn get_users_rest<'a>(
+    instance: &'a Octocrab,
+    usernames: &'a [&'a str],
+    concurrency: usize,
+) -> impl Stream<Item = octocrab::Result<models::ExtendedUser>> + 'a {
+    stream::iter(usernames.iter().copied())
+        .map(move |username| async move {
+            match get_user(instance, username).await {
+                Ok(user) => Ok(Some(user)),
+                // Accounts may have been deleted (e.g. since opening a pull request)
+                Err(octocrab::Error::GitHub { source, .. }) if source.errors.is_none() => {
+                    log::warn!("Could not load user information for {}", username);
+                    Ok(None)
+                }
+                Err(other) => Err(other),
+            }
+        })
+        .buffer_unordered(concurrency)
+        .try_filter_map(future::ok)
 }
 
-pub enum BlockStatus {
-    NewlyBlocked,
-    AlreadyBlocked,
-    UserNotFound,
-    OtherSuccess(StatusCode),
-    OtherNonSuccess(String),
+/// Load user information via individual REST requests (accounts that can't be found are skipped)
+pub fn get_users_info_rest<'a>(
+    instance: &'a Octocrab,
+    usernames: &'a [&'a str],
+    concurrency: usize,
+) -> impl Stream<Item = octocrab::Result<models::UserInfo>> + 'a {
+    get_users_rest(instance, usernames, concurrency).map_ok(Into::into)
 }
 
-impl BlockStatus {
-    fn from_status_code_result(
-        status_code_result: octocrab::Result<StatusCodeWrapper>,
-    ) -> octocrab::Result<Self> {
-        match status_code_result {
-            Ok(StatusCodeWrapper(status_code)) if status_code == StatusCode::NO_CONTENT => {
-                Ok(BlockStatus::NewlyBlocked)
-            }
-            Ok(StatusCodeWrapper(status_code)) => Ok(BlockStatus::OtherSuccess(status_code)),
-            Err(octocrab::Error::GitHub { source, .. }) if source.errors.is_none() => {
-                Ok(if source.message.contains(BLOCK_304_MESSAGE) {
-                    BlockStatus::AlreadyBlocked
-                } else if source.message.contains(BLOCK_404_MESSAGE) {
-                    BlockStatus::UserNotFound
-                } else {
-                    BlockStatus::OtherNonSuccess(source.message)
-                })
-            }
-            Err(other) => Err(other),
+/// Load user information via GraphQL, falling back to REST requests if GraphQL is unavailable
+pub async fn get_users_info_with_fallback(
+    instance: &Octocrab,
+    usernames: &[&str],
+    chunk_size: usize,
+    retry: RetryConfig,
+    concurrency: usize,
+    continue_on_error: bool,
+) -> octocrab::Result<Vec<models::UserInfo>> {
+    match get_users_info_chunked_with_retries(
+        instance,
+        usernames,
+        chunk_size,
+        retry,
+        continue_on_error,
+    )
+    .try_collect()
+    .await
+    {
+        Err(error) if is_graphql_unavailable(&error) => {
+            log::warn!(
+                "GraphQL is unavailable ({}); falling back to {} REST requests",
+                error,
+                usernames.len()
+            );
+
+            get_users_info_rest(instance, usernames, concurrency)
+                .try_collect()
+                .await
         }
+        other => other,
     }
 }
 
-/// Block a user from either an organization or a user account
-pub async fn block_user(
-    instance: &Octocrab,
-    organization: Option<&str>,
-    username: &str,
-) -> octocrab::Result<BlockStatus> {
-    match organization {
-        Some(value) => block_user_for_organization(instance, value, username).await,
-        None => block_user_for_user(instance, username).await,
-    }
+const GRAPHQL_FOLLOWERS_PAGE_SIZE: u8 = 100;
+
+#[derive(Deserialize)]
+struct GraphQlFollowersResults {
+    data: GraphQlFollowersData,
+}
+
+#[derive(Deserialize)]
+struct GraphQlFollowersData {
+    user: Option<GraphQlFollowersUser>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlFollowersUser {
+    followers: GraphQlConnection<models::UserInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlConnection<T> {
+    page_info: GraphQlPageInfo,
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlPageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+/// Stream a user's followers (including account creation dates) via GraphQL
+///
+/// This requires fewer requests than `get_followers`, but is only available to authenticated users.
+/// GitHub reports an unknown login as a null user, which is returned as a `UserNotFound` error.
+pub fn get_followers_graphql<'a>(
+    instance: &'a Octocrab,
+    username: &'a str,
+) -> impl Stream<Item = error::Result<models::UserInfo>> + 'a {
+    get_followers_graphql_with_retries(instance, username, RetryConfig::NONE)
+}
+
+pub(crate) fn get_followers_graphql_with_retries<'a>(
+    instance: &'a Octocrab,
+    username: &'a str,
+    retry: RetryConfig,
+) -> impl Stream<Item = error::Result<models::UserInfo>> + 'a {
+    // The state is `None` after the last page, and otherwise contains the cursor (if any)
+    stream::try_unfold(
+        Some(None),
+        move |cursor: Option<Option<String>>| async move {
+            match cursor {
+                Some(cursor) => {
+                    let after = cursor
+                        .map(|value| format!(", after: {}", serde_json::Value::String(value)))
+                        .unwrap_or_default();
+                    let query = format!(
+                        "query {{ user(login: {}) {{ followers(first: {}{}) {{ \
+                            pageInfo {{ hasNextPage endCursor }} \
+                            nodes {{ login databaseId createdAt name twitterUsername }} }} }} }}",
+                        serde_json::Value::String(username.to_string()),
+                        GRAPHQL_FOLLOWERS_PAGE_SIZE,
+                        after
+                    );
+
+                    let results: GraphQlFollowersResults = retry
+                        .run(&format!("GraphQL follower query for {}", username), || {
+                            graphql(instance, &query)
+                        })
+                        .await?;
+
+                    let user = results.data.user.ok_or_else(|| Error::UserNotFound {
+                        login: username.to_string(),
+                    })?;
+                    let GraphQlConnection { page_info, nodes } = user.followers;
+                    let next = if page_info.has_next_page {
+                        page_info.end_cursor.map(Some)
+                    } else {
+                        None
+                    };
+
+                    Ok::<_, Error>(Some((nodes, next)))
+                }
+                None => Ok(None),
+            }
+        },
+    )
+    .map_ok(|nodes| stream::iter(nodes.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+/// Get extended information for a user
+pub async fn get_user(
+    instance: &Octocrab,
+    username: &str,
+) -> octocrab::Result<models::ExtendedUser> {
+    let route = format!("users/{}", username);
+
+    instance
+        .get::<models::ExtendedUser, _, ()>(route, None)
+        .await
+}
+
+/// Load the extended profile of the authenticated user
+pub async fn get_current_user(instance: &Octocrab) -> octocrab::Result<models::ExtendedUser> {
+    instance
+        .get::<models::ExtendedUser, _, ()>("user", None)
+        .await
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockStatus {
+    NewlyBlocked,
+    AlreadyBlocked,
+    UserNotFound,
+    RateLimited { retry_after: Option<Duration> },
+    OtherSuccess(StatusCode),
+    OtherNonSuccess(String),
+}
+
+impl BlockStatus {
+    fn from_status_code_result(
+        status_and_headers: &StatusAndHeaders,
+        result: octocrab::Result<()>,
+    ) -> octocrab::Result<Self> {
+        match result {
+            Ok(()) => Ok(Self::from_status_and_message(status_and_headers, None)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.errors.is_none() => Ok(
+                Self::from_status_and_message(status_and_headers, Some(source.message)),
+            ),
+            Err(other) => Err(other),
+        }
+    }
+
+    /// Map a response to a status, given the GitHub error message if the request failed
+    pub(crate) fn from_status_and_message(
+        status_and_headers: &StatusAndHeaders,
+        message: Option<String>,
+    ) -> Self {
+        match message {
+            None if status_and_headers.status_code == StatusCode::NO_CONTENT => {
+                BlockStatus::NewlyBlocked
+            }
+            None => BlockStatus::OtherSuccess(status_and_headers.status_code),
+            Some(message) if is_rate_limited(status_and_headers, &message) => {
+                BlockStatus::RateLimited {
+                    retry_after: status_and_headers
+                        .retry_after()
+                        .or_else(|| status_and_headers.until_rate_limit_reset()),
+                }
+            }
+            Some(message) if message.contains(BLOCK_304_MESSAGE) => BlockStatus::AlreadyBlocked,
+            Some(message) if message.contains(BLOCK_404_MESSAGE) => BlockStatus::UserNotFound,
+            Some(message) => BlockStatus::OtherNonSuccess(message),
+        }
+    }
+}
+
+fn is_rate_limited(status_and_headers: &StatusAndHeaders, message: &str) -> bool {
+    (status_and_headers.status_code == StatusCode::FORBIDDEN
+        || status_and_headers.status_code == StatusCode::TOO_MANY_REQUESTS)
+        && (status_and_headers.retry_after().is_some()
+            || status_and_headers.rate_limit_remaining() == Some(0)
+            || RATE_LIMIT_MESSAGES
+                .iter()
+                .any(|pattern| message.contains(pattern)))
+}
+
+/// Block a user from either an organization or a user account
+pub async fn block_user(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    username: &str,
+) -> error::Result<BlockStatus> {
+    Ok(block_user_with_headers(instance, organization, username)
+        .await?
+        .0)
+}
+
+/// Block a user, also returning the response's status and headers (e.g. for rate limit pacing)
+pub async fn block_user_with_headers(
+    instance: &Octocrab,
+    organization: Option<&str>,
+    username: &str,
+) -> error::Result<(BlockStatus, StatusAndHeaders)> {
+    match organization {
+        Some(value) => block_user_for_organization_with_headers(instance, value, username).await,
+        None => Ok(block_user_for_user_with_headers(instance, username).await?),
+    }
+}
+
+fn block_route(organization: Option<&str>, username: &str) -> String {
+    match organization {
+        Some(organization) => format!("orgs/{}/blocks/{}", organization, username),
+        None => format!("user/blocks/{}", username),
+    }
 }
 
 /// Block a user and indicate the result of the operation
@@ -201,11 +1178,21 @@ pub async fn block_user_for_user(
     instance: &Octocrab,
     username: &str,
 ) -> octocrab::Result<BlockStatus> {
-    let route = format!("/user/blocks/{}", username);
+    Ok(block_user_for_user_with_headers(instance, username)
+        .await?
+        .0)
+}
 
-    BlockStatus::from_status_code_result(
-        instance.put::<StatusCodeWrapper, _, ()>(route, None).await,
-    )
+async fn block_user_for_user_with_headers(
+    instance: &Octocrab,
+    username: &str,
+) -> octocrab::Result<(BlockStatus, StatusAndHeaders)> {
+    let route = block_route(None, username);
+
+    let (status_and_headers, result) = put_for_status(instance, &route).await?;
+    let status = BlockStatus::from_status_code_result(&status_and_headers, result)?;
+
+    Ok((status, status_and_headers))
 }
 
 /// Block a user from an organization
@@ -213,61 +1200,408 @@ pub async fn block_user_for_organization(
     instance: &Octocrab,
     organization: &str,
     username: &str,
-) -> octocrab::Result<BlockStatus> {
-    let route = format!("/orgs/{}/blocks/{}", organization, username);
-
-    BlockStatus::from_status_code_result(
-        instance.put::<StatusCodeWrapper, _, ()>(route, None).await,
+) -> error::Result<BlockStatus> {
+    Ok(
+        block_user_for_organization_with_headers(instance, organization, username)
+            .await?
+            .0,
     )
 }
 
-pub fn get_followers(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
-    let route = "user/followers";
-    let opts = vec![("per_page", FOLLOWERS_PAGE_SIZE)];
+async fn block_user_for_organization_with_headers(
+    instance: &Octocrab,
+    organization: &str,
+    username: &str,
+) -> error::Result<(BlockStatus, StatusAndHeaders)> {
+    let route = block_route(Some(organization), username);
 
-    stream::once(async move { instance.get::<Page<User>, _, _>(route, Some(&opts)).await })
-        .and_then(move |page| future::ok(pager_stream(instance, page)))
-        .try_flatten()
+    let (status_and_headers, result) = put_for_status(instance, &route).await?;
+
+    match result {
+        Err(error) if status_and_headers.is_sso_required() => {
+            Err(status_and_headers.map_org_error(organization, error))
+        }
+        other => {
+            let status = BlockStatus::from_status_code_result(&status_and_headers, other)?;
+
+            Ok((status, status_and_headers))
+        }
+    }
+}
+
+pub fn get_followers(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+    get_user_list(
+        instance,
+        "user/followers",
+        PagingOptions::new(FOLLOWERS_PAGE_SIZE),
+    )
 }
 
 pub fn get_following(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
-    let route = "user/following";
-    let opts = vec![("per_page", FOLLOWING_PAGE_SIZE)];
+    get_user_list(
+        instance,
+        "user/following",
+        PagingOptions::new(FOLLOWING_PAGE_SIZE),
+    )
+}
 
-    stream::once(async move { instance.get::<Page<User>, _, _>(route, Some(&opts)).await })
-        .and_then(move |page| future::ok(pager_stream(instance, page)))
-        .try_flatten()
+/// Stream the users listed at a route for the authenticated user
+pub(crate) fn get_user_list<'a>(
+    instance: &'a Octocrab,
+    route: &'a str,
+    paging: PagingOptions,
+) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+
+    stream::once(async move {
+        paging
+            .retry
+            .run(route, || {
+                instance.get::<Page<User>, _, _>(route, Some(&opts))
+            })
+            .await
+    })
+    .and_then(move |page| future::ok(pager_stream_with_options(instance, page, paging)))
+    .try_flatten()
+}
+
+/// Stream the followers of any user
+pub fn get_followers_for<'a>(
+    instance: &'a Octocrab,
+    username: &'a str,
+) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+    get_followers_for_with_options(instance, username, PagingOptions::new(FOLLOWERS_PAGE_SIZE))
+}
+
+pub(crate) fn get_followers_for_with_options<'a>(
+    instance: &'a Octocrab,
+    username: &'a str,
+    paging: PagingOptions,
+) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+
+    stream::once(async move {
+        let route = format!("users/{}/followers", username);
+
+        paging
+            .retry
+            .run(&route, || {
+                instance.get::<Page<User>, _, _>(&route, Some(&opts))
+            })
+            .await
+    })
+    .and_then(move |page| future::ok(pager_stream_with_options(instance, page, paging)))
+    .try_flatten()
+}
+
+/// Stream pages of the authenticated user's followers, optionally resuming from a page URL
+pub fn get_followers_pages<'a>(
+    instance: &'a Octocrab,
+    resume_from: Option<&'a str>,
+) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+    get_user_list_pages(
+        instance,
+        "user/followers",
+        resume_from,
+        PagingOptions::new(FOLLOWERS_PAGE_SIZE),
+    )
+}
+
+/// Stream pages of accounts the authenticated user follows, optionally resuming from a page URL
+pub fn get_following_pages<'a>(
+    instance: &'a Octocrab,
+    resume_from: Option<&'a str>,
+) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+    get_user_list_pages(
+        instance,
+        "user/following",
+        resume_from,
+        PagingOptions::new(FOLLOWING_PAGE_SIZE),
+    )
+}
+
+/// Stream pages of the users listed at a route for the authenticated user, optionally resuming
+/// from a page URL
+pub(crate) fn get_user_list_pages<'a>(
+    instance: &'a Octocrab,
+    route: &'a str,
+    resume_from: Option<&'a str>,
+    paging: PagingOptions,
+) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+    let first = move || async move {
+        let opts = [("per_page", paging.page_size)];
+        Ok(instance.get(route, Some(&opts)).await?)
+    };
+
+    resumable_page_stream(instance, route.to_string(), first, resume_from, paging)
+}
+
+/// Stream pages of blocked accounts for a user or organization, optionally resuming from a page URL
+pub fn get_blocks_pages<'a>(
+    instance: &'a Octocrab,
+    organization: Option<&'a str>,
+    resume_from: Option<&'a str>,
+) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+    get_blocks_pages_with_options(
+        instance,
+        organization,
+        resume_from,
+        PagingOptions::new(BLOCKS_PAGE_SIZE),
+    )
+}
+
+pub(crate) fn get_blocks_pages_with_options<'a>(
+    instance: &'a Octocrab,
+    organization: Option<&'a str>,
+    resume_from: Option<&'a str>,
+    paging: PagingOptions,
+) -> impl Stream<Item = error::Result<Page<User>>> + 'a {
+    let route = match organization {
+        Some(value) => format!("orgs/{}/blocks", value),
+        None => "user/blocks".to_string(),
+    };
+    let first_route = route.clone();
+    let first = move || {
+        let route = first_route.clone();
+
+        async move {
+            let opts = [("per_page", paging.page_size)];
+
+            match organization {
+                Some(value) => get_org_page(instance, value, &route, &opts).await,
+                None => Ok(instance.get(route, Some(&opts)).await?),
+            }
+        }
+    };
+
+    resumable_page_stream(instance, route, first, resume_from, paging)
 }
 
 pub fn get_blocks<'a>(
     instance: &'a Octocrab,
     organization: Option<&'a str>,
-) -> LocalBoxStream<'a, octocrab::Result<User>> {
+) -> LocalBoxStream<'a, error::Result<User>> {
+    get_blocks_with_options(instance, organization, PagingOptions::new(BLOCKS_PAGE_SIZE))
+}
+
+pub(crate) fn get_blocks_with_options<'a>(
+    instance: &'a Octocrab,
+    organization: Option<&'a str>,
+    paging: PagingOptions,
+) -> LocalBoxStream<'a, error::Result<User>> {
     match organization {
-        Some(value) => Box::pin(get_blocks_for_organization(instance, value)),
-        None => Box::pin(get_blocks_for_user(instance)),
+        Some(value) => Box::pin(get_org_user_list(
+            instance,
+            value,
+            format!("orgs/{}/blocks", value),
+            paging,
+        )),
+        None => Box::pin(get_user_list(instance, "user/blocks", paging).map_err(Error::from)),
     }
 }
 
 pub fn get_blocks_for_user(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
-    let route = "user/blocks";
-    let opts = vec![("per_page", BLOCKS_PAGE_SIZE)];
+    get_user_list(
+        instance,
+        "user/blocks",
+        PagingOptions::new(BLOCKS_PAGE_SIZE),
+    )
+}
 
-    stream::once(async move { instance.get::<Page<User>, _, _>(route, Some(&opts)).await })
-        .and_then(move |page| future::ok(pager_stream(instance, page)))
+pub fn get_blocks_for_organization<'a>(
+    instance: &'a Octocrab,
+    organization: &'a str,
+) -> impl Stream<Item = error::Result<User>> + 'a {
+    get_org_user_list(
+        instance,
+        organization,
+        format!("orgs/{}/blocks", organization),
+        PagingOptions::new(BLOCKS_PAGE_SIZE),
+    )
+}
+
+/// Stream the users listed at a route for an organization, surfacing SSO authorization failures
+fn get_org_user_list<'a>(
+    instance: &'a Octocrab,
+    organization: &'a str,
+    route: String,
+    paging: PagingOptions,
+) -> impl Stream<Item = error::Result<User>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+
+    stream::once(async move { get_org_page::<User>(instance, organization, &route, &opts).await })
+        .and_then(move |page| {
+            future::ok(pager_stream_with_options(instance, page, paging).map_err(Error::from))
+        })
         .try_flatten()
 }
 
-pub fn get_blocks_for_organization<'a>(
+/// Get the first page of results for an organization resource, surfacing SSO authorization failures
+async fn get_org_page<R: DeserializeOwned>(
+    instance: &Octocrab,
+    organization: &str,
+    route: &str,
+    opts: &[(&str, u8)],
+) -> error::Result<Page<R>> {
+    let response = instance
+        ._get(instance.absolute_url(route)?, Some(opts))
+        .await?;
+    let status_and_headers = StatusAndHeaders::new(&response);
+
+    match octocrab::map_github_error(response).await {
+        Ok(response) => Ok(Page::<R>::from_response(response).await?),
+        Err(error) => Err(status_and_headers.map_org_error(organization, error)),
+    }
+}
+
+/// Check that the authenticated user is an active member of an organization
+///
+/// GitHub responds with a 404 both for organizations that don't exist and for those the user doesn't
+/// belong to, so this gives a clearer error up front than organization endpoints would later.
+pub async fn check_org_membership(
+    instance: &Octocrab,
+    organization: &str,
+) -> error::Result<models::OrgMembership> {
+    let route = format!("user/memberships/orgs/{}", organization);
+    let response = instance
+        ._get(instance.absolute_url(route)?, None::<&()>)
+        .await?;
+    let status_and_headers = StatusAndHeaders::new(&response);
+
+    let membership = match octocrab::map_github_error(response).await {
+        Ok(response) => models::OrgMembership::from_response(response).await?,
+        Err(_) if status_and_headers.status_code == StatusCode::NOT_FOUND => {
+            return Err(Error::NotOrgMember {
+                organization: organization.to_string(),
+            })
+        }
+        Err(error) => return Err(status_and_headers.map_org_error(organization, error)),
+    };
+
+    if membership.state == "active" {
+        Ok(membership)
+    } else {
+        Err(Error::NotOrgMember {
+            organization: organization.to_string(),
+        })
+    }
+}
+
+/// Stream the repositories belonging to an organization
+pub fn get_org_repos<'a>(
+    instance: &'a Octocrab,
+    organization: &'a str,
+) -> impl Stream<Item = octocrab::Result<Repository>> + 'a {
+    get_org_repos_with_options(instance, organization, PagingOptions::new(REPOS_PAGE_SIZE))
+}
+
+pub(crate) fn get_org_repos_with_options<'a>(
     instance: &'a Octocrab,
     organization: &'a str,
+    paging: PagingOptions,
+) -> impl Stream<Item = octocrab::Result<Repository>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+
+    stream::once(async move {
+        let route = format!("orgs/{}/repos", organization);
+
+        paging
+            .retry
+            .run(&route, || {
+                instance.get::<Page<Repository>, _, _>(&route, Some(&opts))
+            })
+            .await
+    })
+    .and_then(move |page| future::ok(pager_stream_with_options(instance, page, paging)))
+    .try_flatten()
+}
+
+/// Stream the users who have starred a repository
+pub fn get_stargazers<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
 ) -> impl Stream<Item = octocrab::Result<User>> + 'a {
-    let route = format!("orgs/{}/blocks", organization);
-    let opts = vec![("per_page", BLOCKS_PAGE_SIZE)];
+    get_stargazers_with_options(
+        instance,
+        owner,
+        repo,
+        PagingOptions::new(STARGAZERS_PAGE_SIZE),
+    )
+}
 
-    stream::once(async move { instance.get::<Page<User>, _, _>(route, Some(&opts)).await })
-        .and_then(move |page| future::ok(pager_stream(instance, page)))
-        .try_flatten()
+pub(crate) fn get_stargazers_with_options<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
+    paging: PagingOptions,
+) -> impl Stream<Item = octocrab::Result<User>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+
+    stream::once(async move {
+        let route = format!("repos/{}/{}/stargazers", owner, repo);
+
+        paging
+            .retry
+            .run(&route, || {
+                instance.get::<Page<User>, _, _>(&route, Some(&opts))
+            })
+            .await
+    })
+    .and_then(move |page| future::ok(pager_stream_with_options(instance, page, paging)))
+    .try_flatten()
+}
+
+/// Stream the users who have starred a repository, together with when they starred it
+///
+/// The timestamps are only provided by GitHub when a custom media type is requested.
+pub fn get_stargazers_with_timestamps<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
+) -> impl Stream<Item = octocrab::Result<StarGazer>> + 'a {
+    get_stargazers_with_timestamps_with_options(
+        instance,
+        owner,
+        repo,
+        PagingOptions::new(STARGAZERS_PAGE_SIZE),
+    )
+}
+
+pub(crate) fn get_stargazers_with_timestamps_with_options<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
+    paging: PagingOptions,
+) -> impl Stream<Item = octocrab::Result<StarGazer>> + 'a {
+    let opts = vec![("per_page", paging.page_size)];
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static(STAR_MEDIA_TYPE));
+
+    let first_headers = headers.clone();
+
+    stream::once(async move {
+        let route = format!("repos/{}/{}/stargazers", owner, repo);
+
+        paging
+            .retry
+            .run(&route, || {
+                instance.get_with_headers::<Page<StarGazer>, _, _>(
+                    &route,
+                    Some(&opts),
+                    Some(first_headers.clone()),
+                )
+            })
+            .await
+    })
+    .and_then(move |page| {
+        future::ok(
+            page_stream_with_options(instance, page, Some(headers.clone()), paging)
+                .and_then(|mut page| future::ok(stream::iter(page.take_items()).map(Ok)))
+                .try_flatten(),
+        )
+    })
+    .try_flatten()
 }
 
 #[derive(Default)]
@@ -275,11 +1609,18 @@ pub struct Exclusions(HashMap<String, HashSet<String>>);
 
 impl Exclusions {
     pub fn load<R: Read>(reader: R) -> csv::Result<Exclusions> {
+        Self::load_with_delimiter(reader, DEFAULT_CSV_DELIMITER)
+    }
+
+    pub fn load_with_delimiter<R: Read>(reader: R, delimiter: u8) -> csv::Result<Exclusions> {
         let mut csv_reader = csv::ReaderBuilder::new()
             .has_headers(false)
+            .delimiter(delimiter)
             .from_reader(reader);
+        // Repository paths and usernames are both case-insensitive on GitHub
         let mut pairs = csv_reader
             .deserialize::<(String, String)>()
+            .map(|pair| pair.map(|(repo, username)| (repo.to_lowercase(), username.to_lowercase())))
             .collect::<csv::Result<Vec<_>>>()?;
         pairs.sort_unstable_by(|(repo1, _), (repo2, _)| repo1.cmp(repo2));
 
@@ -288,12 +1629,7 @@ impl Exclusions {
                 .into_iter()
                 .group_by(|(repo, _)| repo.clone())
                 .into_iter()
-                .map(|(repo, pairs)| {
-                    (
-                        repo,
-                        pairs.map(|(_, username)| username.to_lowercase()).collect(),
-                    )
-                })
+                .map(|(repo, pairs)| (repo, pairs.map(|(_, username)| username).collect()))
                 .collect(),
         ))
     }
@@ -303,8 +1639,451 @@ impl Exclusions {
         // All other exclusions should be managed with an exclusions file
         username == "ghost"
             || username == "dependabot[bot]"
-            || self.0.get(repo).map_or(false, |usernames| {
-                usernames.contains(&username.to_lowercase())
-            })
+            || self
+                .0
+                .get(&repo.to_lowercase())
+                .is_some_and(|usernames| usernames.contains(&username.to_lowercase()))
+    }
+
+    /// The (lowercased) usernames excluded for a repository, in sorted order
+    pub fn usernames(&self, repo: &str) -> Vec<&str> {
+        let mut usernames = self
+            .0
+            .get(&repo.to_lowercase())
+            .map(|usernames| usernames.iter().map(String::as_str).collect::<Vec<_>>())
+            .unwrap_or_default();
+        usernames.sort_unstable();
+        usernames
+    }
+
+    /// Remove an exclusion, returning whether it was present
+    pub fn remove(&mut self, repo: &str, username: &str) -> bool {
+        let repo = repo.to_lowercase();
+
+        match self.0.get_mut(&repo) {
+            Some(usernames) => {
+                let removed = usernames.remove(&username.to_lowercase());
+
+                if usernames.is_empty() {
+                    self.0.remove(&repo);
+                }
+
+                removed
+            }
+            None => false,
+        }
+    }
+
+    pub fn save<W: Write>(&self, writer: W) -> csv::Result<()> {
+        self.save_with_delimiter(writer, DEFAULT_CSV_DELIMITER)
+    }
+
+    /// Write the exclusions (lowercased and sorted) in the same format they're loaded from
+    pub fn save_with_delimiter<W: Write>(&self, writer: W, delimiter: u8) -> csv::Result<()> {
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(writer);
+        let mut repos = self.0.keys().collect::<Vec<_>>();
+        repos.sort_unstable();
+
+        for repo in repos {
+            for username in self.usernames(repo) {
+                csv_writer.write_record([repo.as_str(), username])?;
+            }
+        }
+
+        Ok(csv_writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Serve one JSON body per connection on a local port, returning the base URL and a handle
+    /// that yields the bodies of the requests received
+    fn serve_json(bodies: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        serve_responses(bodies.into_iter().map(|body| (200, body)).collect())
+    }
+
+    /// Serve one response (a status code and JSON body) per connection on a local port
+    ///
+    /// The listener is closed after the last response, so any further request fails.
+    fn serve_responses(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status_code, body)| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(&stream);
+                    let mut content_length = 0;
+
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+
+                        if line == "\r\n" {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+
+                    let mut request = vec![0; content_length];
+                    reader.read_exact(&mut request).unwrap();
+
+                    write!(
+                        &stream,
+                        "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status_code,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+
+        (base_url, handle)
+    }
+
+    #[tokio::test]
+    async fn get_followers_graphql_follows_cursors() {
+        let (base_url, handle) = serve_json(vec![
+            r#"{"data": {"user": {"followers": {
+                "pageInfo": {"hasNextPage": true, "endCursor": "abc"},
+                "nodes": [{"login": "alice", "databaseId": 1, "createdAt": "2020-01-01T00:00:00Z",
+                    "name": null, "twitterUsername": null}]}}}}"#,
+            r#"{"data": {"user": {"followers": {
+                "pageInfo": {"hasNextPage": false, "endCursor": "def"},
+                "nodes": [{"login": "bob", "databaseId": 2, "createdAt": "2021-01-01T00:00:00Z",
+                    "name": "Bob", "twitterUsername": null}]}}}}"#,
+        ]);
+        let instance = init_with_base_url(None, Some(&base_url)).unwrap();
+
+        let followers = get_followers_graphql(&instance, "octocat")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let requests = handle.join().unwrap();
+
+        assert_eq!(
+            followers
+                .iter()
+                .map(|info| (info.login.as_str(), info.database_id))
+                .collect::<Vec<_>>(),
+            vec![("alice", Some(1)), ("bob", Some(2))]
+        );
+        assert!(!requests[0].contains("after"));
+        assert!(requests[1].contains(r#"after: \"abc\""#));
+    }
+
+    #[tokio::test]
+    async fn get_followers_graphql_fails_for_unknown_user() {
+        let (base_url, handle) = serve_json(vec![
+            r#"{"data": {"user": null}, "errors": [{"type": "NOT_FOUND",
+                "message": "Could not resolve to a User with the login of 'nobody'."}]}"#,
+        ]);
+        let instance = init_with_base_url(None, Some(&base_url)).unwrap();
+
+        let result = get_followers_graphql(&instance, "nobody")
+            .try_collect::<Vec<_>>()
+            .await;
+        handle.join().unwrap();
+
+        match result {
+            Err(Error::UserNotFound { login }) => assert_eq!(login, "nobody"),
+            other => panic!("Expected a user not found error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn graphql_unavailable_requires_exact_status_and_message() {
+        assert!(is_graphql_unavailable_response(
+            StatusCode::NOT_FOUND,
+            "Not Found"
+        ));
+        assert!(!is_graphql_unavailable_response(
+            StatusCode::FORBIDDEN,
+            "Resource not accessible by integration"
+        ));
+        assert!(!is_graphql_unavailable_response(
+            StatusCode::FORBIDDEN,
+            "Repository access blocked: forbidden by policy"
+        ));
+        assert!(!is_graphql_unavailable_response(
+            StatusCode::UNAUTHORIZED,
+            "Not Found"
+        ));
+    }
+
+    #[test]
+    fn is_graphql_unavailable_only_matches_unavailable_errors() {
+        let unavailable = error::GraphQlUnavailable {
+            status_code: 404,
+            message: "Not Found".to_string(),
+        }
+        .into_octocrab_error();
+        let timeout = error::Timeout {
+            operation: "GraphQL query".to_string(),
+            duration: Duration::from_secs(1),
+        }
+        .into_octocrab_error();
+
+        assert!(is_graphql_unavailable(&unavailable));
+        assert!(!is_graphql_unavailable(&timeout));
+    }
+
+    #[tokio::test]
+    async fn resume_page_rejects_foreign_urls() {
+        let instance = init_with_base_url(None, Some("https://github.example.com/api/v3")).unwrap();
+
+        for url in [
+            "https://evil.example/api/v3/user/followers?page=2",
+            "http://github.example.com/api/v3/user/followers?page=2",
+            "https://github.example.com:8443/api/v3/user/followers?page=2",
+        ] {
+            match get_resume_page::<User>(&instance, url).await {
+                Err(Error::ForeignResumeUrl { .. }) => {}
+                other => panic!("Expected a foreign resume URL error, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_page_does_not_retry_expired_urls() {
+        let (base_url, handle) = serve_responses(vec![(
+            422,
+            r#"{"message": "Validation Failed", "documentation_url": null}"#,
+        )]);
+        let instance = init_with_base_url(None, Some(&base_url)).unwrap();
+        let retry = RetryConfig {
+            retries: 3,
+            ..RetryConfig::NONE
+        };
+
+        let result = get_resume_page_with_retries::<User>(
+            &instance,
+            &format!("{}user/followers?page=2", base_url),
+            retry,
+        )
+        .await;
+
+        // A retry would fail to connect, since only one response is served
+        assert_eq!(handle.join().unwrap().len(), 1);
+        match result {
+            Err(Error::InvalidResumeUrl { .. }) => {}
+            other => panic!("Expected an invalid resume URL error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn exclusions_match_repo_paths_case_insensitively() {
+        let exclusions = Exclusions::load("MyOrg/Repo,SomeUser\n".as_bytes()).unwrap();
+
+        assert!(exclusions.is_excluded("myorg/repo", "someuser"));
+        assert!(exclusions.is_excluded("MYORG/REPO", "SomeUser"));
+        assert!(exclusions.is_excluded("MyOrg/Repo", "SOMEUSER"));
+        assert!(!exclusions.is_excluded("myorg/other", "someuser"));
+    }
+
+    #[test]
+    fn exclusions_round_trip_after_removal() {
+        let mut exclusions =
+            Exclusions::load("b/repo,Zed\na/repo,SomeUser\na/repo,other\n".as_bytes()).unwrap();
+
+        assert!(exclusions.remove("A/Repo", "SOMEUSER"));
+        assert!(!exclusions.remove("a/repo", "missing"));
+
+        let mut output = vec![];
+        exclusions.save(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a/repo,other\nb/repo,zed\n"
+        );
+    }
+
+    #[test]
+    fn graphql_user_results_include_follow_counts() {
+        let response = r#"{
+            "data": {
+                "u0": {
+                    "login": "octocat",
+                    "createdAt": "2011-01-25T18:44:36Z",
+                    "name": "The Octocat",
+                    "twitterUsername": null,
+                    "followers": { "totalCount": 14421 },
+                    "following": { "totalCount": 9 }
+                },
+                "u1": null
+            }
+        }"#;
+
+        let results: GraphQlUserResults = serde_json::from_str(response).unwrap();
+        let info = results.data["u0"].as_ref().unwrap();
+
+        assert_eq!(info.followers.map(|count| count.total_count), Some(14421));
+        assert_eq!(info.following.map(|count| count.total_count), Some(9));
+        assert!(results.data["u1"].is_none());
+    }
+
+    fn status_and_headers(status_code: StatusCode) -> StatusAndHeaders {
+        StatusAndHeaders {
+            status_code,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    #[test]
+    fn block_status_maps_no_content_to_newly_blocked() {
+        assert_eq!(
+            BlockStatus::from_status_and_message(&status_and_headers(StatusCode::NO_CONTENT), None),
+            BlockStatus::NewlyBlocked
+        );
+    }
+
+    #[test]
+    fn block_status_maps_already_blocked_message() {
+        let message = Some(BLOCK_304_MESSAGE.to_string());
+
+        assert_eq!(
+            BlockStatus::from_status_and_message(
+                &status_and_headers(StatusCode::UNPROCESSABLE_ENTITY),
+                message
+            ),
+            BlockStatus::AlreadyBlocked
+        );
+    }
+
+    #[test]
+    fn block_status_maps_not_found_message() {
+        let message = Some(BLOCK_404_MESSAGE.to_string());
+
+        assert_eq!(
+            BlockStatus::from_status_and_message(
+                &status_and_headers(StatusCode::NOT_FOUND),
+                message
+            ),
+            BlockStatus::UserNotFound
+        );
+    }
+
+    #[test]
+    fn block_status_maps_unknown_message_to_other_non_success() {
+        let message = Some("Something went wrong".to_string());
+
+        assert_eq!(
+            BlockStatus::from_status_and_message(
+                &status_and_headers(StatusCode::INTERNAL_SERVER_ERROR),
+                message
+            ),
+            BlockStatus::OtherNonSuccess("Something went wrong".to_string())
+        );
+    }
+
+    #[test]
+    fn block_status_maps_rate_limit_message_with_retry_after() {
+        let mut status_and_headers = status_and_headers(StatusCode::FORBIDDEN);
+        status_and_headers
+            .headers
+            .insert(RETRY_AFTER, HeaderValue::from_static("60"));
+        let message = Some("You have exceeded a secondary rate limit".to_string());
+
+        assert_eq!(
+            BlockStatus::from_status_and_message(&status_and_headers, message),
+            BlockStatus::RateLimited {
+                retry_after: Some(Duration::from_secs(60))
+            }
+        );
+    }
+
+    #[test]
+    fn explicit_base_url_takes_precedence_over_environment() {
+        let from_env = Some("https://github.example.com/api/v3".to_string());
+
+        assert_eq!(
+            resolve_base_url(Some("https://other.example.com/api/v3/"), from_env.clone()),
+            Some("https://other.example.com/api/v3/".to_string())
+        );
+        assert_eq!(
+            resolve_base_url(None, from_env),
+            Some("https://github.example.com/api/v3/".to_string())
+        );
+        assert_eq!(resolve_base_url(None, Some("".to_string())), None);
+        assert_eq!(resolve_base_url(None, None), None);
+    }
+
+    #[test]
+    fn github_api_url_environment_variable_sets_base_url() {
+        let from_env = Some("https://github.example.com/api/v3".to_string());
+        let instance = build_instance(None, resolve_base_url(None, from_env)).unwrap();
+
+        assert_eq!(
+            instance.absolute_url("user").unwrap().as_str(),
+            "https://github.example.com/api/v3/user"
+        );
+        assert_eq!(
+            graphql_url(&instance).unwrap().as_str(),
+            "https://github.example.com/api/graphql"
+        );
+    }
+
+    #[test]
+    fn rest_routes_are_resolved_under_enterprise_base_path() {
+        let instance = init_with_base_url(None, Some("https://github.example.com/api/v3")).unwrap();
+        let resolve = |route: String| instance.absolute_url(route).unwrap().to_string();
+
+        assert_eq!(
+            resolve(block_route(None, "octocat")),
+            "https://github.example.com/api/v3/user/blocks/octocat"
+        );
+        assert_eq!(
+            resolve(block_route(Some("github"), "octocat")),
+            "https://github.example.com/api/v3/orgs/github/blocks/octocat"
+        );
+        assert_eq!(
+            resolve(check_follow_route("a", "b")),
+            "https://github.example.com/api/v3/users/a/following/b"
+        );
+    }
+
+    #[test]
+    fn graphql_url_is_under_public_api_base() {
+        let instance = init_with_base_url(None, Some("https://api.github.com")).unwrap();
+
+        assert_eq!(
+            graphql_url(&instance).unwrap().as_str(),
+            "https://api.github.com/graphql"
+        );
+    }
+
+    #[test]
+    fn parse_pull_request_path_accepts_both_separators() {
+        let expected = Some(("travisbrown", "octocrabby", 12));
+
+        assert_eq!(
+            parse_pull_request_path("travisbrown/octocrabby#12"),
+            expected
+        );
+        assert_eq!(
+            parse_pull_request_path("travisbrown/octocrabby/12"),
+            expected
+        );
+        assert_eq!(parse_pull_request_path("travisbrown/octocrabby"), None);
+        assert_eq!(parse_pull_request_path("travisbrown/octocrabby#x"), None);
     }
 }
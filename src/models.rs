@@ -1,15 +1,21 @@
 use chrono::{DateTime, Utc};
 use octocrab::models::User;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ExtendedUser {
     pub created_at: DateTime<Utc>,
+    pub name: Option<String>,
+    pub twitter_username: Option<String>,
+    pub email: Option<String>,
+    pub bio: Option<String>,
+    pub followers: u64,
+    pub following: u64,
     #[serde(flatten)]
     pub base: User,
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserInfo {
     pub login: String,
     #[serde(rename = "createdAt")]
@@ -17,4 +23,73 @@ pub struct UserInfo {
     pub name: Option<String>,
     #[serde(rename = "twitterUsername")]
     pub twitter_username: Option<String>,
+    /// The REST API's numeric ID (not included in every query)
+    #[serde(rename = "databaseId", default)]
+    pub database_id: Option<u64>,
+    /// Not included in every query
+    #[serde(default)]
+    pub followers: Option<TotalCount>,
+    /// Not included in every query
+    #[serde(default)]
+    pub following: Option<TotalCount>,
+}
+
+/// The size of a GraphQL connection (such as a user's followers)
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TotalCount {
+    #[serde(rename = "totalCount")]
+    pub total_count: u64,
+}
+
+/// The authenticated user's membership in an organization
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OrgMembership {
+    /// Either `active` or `pending`
+    pub state: String,
+    /// Either `admin` or `member`
+    pub role: String,
+}
+
+impl From<ExtendedUser> for UserInfo {
+    fn from(user: ExtendedUser) -> Self {
+        UserInfo {
+            login: user.base.login,
+            created_at: user.created_at,
+            name: user.name,
+            twitter_username: user.twitter_username,
+            database_id: Some(user.base.id.into_inner()),
+            followers: Some(TotalCount {
+                total_count: user.followers,
+            }),
+            following: Some(TotalCount {
+                total_count: user.following,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserInfo;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn user_info_round_trips_through_json() {
+        let info = UserInfo {
+            login: "octocat".to_string(),
+            created_at: Utc.with_ymd_and_hms(2011, 1, 25, 18, 44, 36).unwrap(),
+            name: Some("The Octocat".to_string()),
+            twitter_username: None,
+            database_id: None,
+            followers: None,
+            following: None,
+        };
+
+        let json = serde_json::to_value(&info).unwrap();
+
+        assert_eq!(json["createdAt"], "2011-01-25T18:44:36Z");
+        assert!(json.get("twitterUsername").is_some());
+        assert!(json.get("created_at").is_none());
+        assert_eq!(serde_json::from_value::<UserInfo>(json).unwrap(), info);
+    }
 }
@@ -0,0 +1,295 @@
+use super::error;
+use chrono::{DateTime, Utc};
+use octocrab::models::User;
+use serde::Serialize;
+use std::io::Write;
+
+/// A row in a user listing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OutputRecord {
+    pub login: String,
+    pub id: u64,
+    /// Account age in days (with -1 for accounts that couldn't be found)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<i64>,
+    /// When the account was created, if requested (with `Some(None)` for accounts that couldn't be
+    /// found)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Option<DateTime<Utc>>>,
+    /// When the account starred a repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starred_at: Option<DateTime<Utc>>,
+}
+
+impl OutputRecord {
+    pub fn new(user: &User) -> Self {
+        OutputRecord {
+            login: user.login.clone(),
+            id: user.id.into_inner(),
+            age: None,
+            created_at: None,
+            starred_at: None,
+        }
+    }
+
+    /// Column names and values (optional fields are only included if present)
+    fn columns(&self) -> Vec<(&'static str, String)> {
+        let mut columns = vec![("login", self.login.clone()), ("id", self.id.to_string())];
+
+        if let Some(age) = self.age {
+            columns.push(("age", age.to_string()));
+        }
+
+        if let Some(created_at) = self.created_at {
+            let value = created_at
+                .map(|value| value.to_rfc3339())
+                .unwrap_or_default();
+            columns.push(("created_at", value));
+        }
+
+        if let Some(starred_at) = self.starred_at {
+            columns.push(("starred_at", starred_at.to_rfc3339()));
+        }
+
+        columns
+    }
+}
+
+/// A destination for output records in some format
+pub trait RecordSink {
+    fn write_record(&mut self, record: &OutputRecord) -> error::Result<()>;
+
+    /// Write anything that's been buffered (such as the end of a JSON array)
+    fn finish(self: Box<Self>) -> error::Result<()>;
+}
+
+/// Supported formats for user listings
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Headerless CSV rows
+    #[default]
+    Csv,
+    /// A single JSON array
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// A Markdown table
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Create a sink for this format (the delimiter is only used for CSV)
+    pub fn sink<'a, W: Write + 'a>(self, writer: W, delimiter: u8) -> Box<dyn RecordSink + 'a> {
+        match self {
+            OutputFormat::Csv => Box::new(CsvSink::new(writer, delimiter)),
+            OutputFormat::Json => Box::new(JsonSink::new(writer)),
+            OutputFormat::Jsonl => Box::new(JsonLinesSink::new(writer)),
+            OutputFormat::Markdown => Box::new(MarkdownSink::new(writer)),
+        }
+    }
+}
+
+pub struct CsvSink<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(writer: W, delimiter: u8) -> Self {
+        CsvSink {
+            writer: csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .flexible(true)
+                .from_writer(writer),
+        }
+    }
+}
+
+impl<W: Write> RecordSink for CsvSink<W> {
+    fn write_record(&mut self, record: &OutputRecord) -> error::Result<()> {
+        Ok(self
+            .writer
+            .write_record(record.columns().into_iter().map(|(_, value)| value))?)
+    }
+
+    fn finish(mut self: Box<Self>) -> error::Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Buffers records so that they can be written as a single array
+pub struct JsonSink<W: Write> {
+    writer: W,
+    records: Vec<OutputRecord>,
+}
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonSink {
+            writer,
+            records: vec![],
+        }
+    }
+}
+
+impl<W: Write> RecordSink for JsonSink<W> {
+    fn write_record(&mut self, record: &OutputRecord) -> error::Result<()> {
+        self.records.push(record.clone());
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> error::Result<()> {
+        serde_json::to_writer(&mut self.writer, &self.records)?;
+        writeln!(self.writer)?;
+        Ok(self.writer.flush()?)
+    }
+}
+
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink { writer }
+    }
+}
+
+impl<W: Write> RecordSink for JsonLinesSink<W> {
+    fn write_record(&mut self, record: &OutputRecord) -> error::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        Ok(writeln!(self.writer)?)
+    }
+
+    fn finish(mut self: Box<Self>) -> error::Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Writes a table whose header is taken from the columns of the first record
+pub struct MarkdownSink<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> MarkdownSink<W> {
+    pub fn new(writer: W) -> Self {
+        MarkdownSink {
+            writer,
+            header_written: false,
+        }
+    }
+}
+
+impl<W: Write> RecordSink for MarkdownSink<W> {
+    fn write_record(&mut self, record: &OutputRecord) -> error::Result<()> {
+        let columns = record.columns();
+
+        if !self.header_written {
+            let names = columns.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+            writeln!(self.writer, "| {} |", names.join(" | "))?;
+            writeln!(self.writer, "|{}", " --- |".repeat(names.len()))?;
+            self.header_written = true;
+        }
+
+        let values = columns
+            .into_iter()
+            .map(|(_, value)| value.replace('|', "\\|"))
+            .collect::<Vec<_>>();
+
+        Ok(writeln!(self.writer, "| {} |", values.join(" | "))?)
+    }
+
+    fn finish(mut self: Box<Self>) -> error::Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_record() -> OutputRecord {
+        OutputRecord {
+            login: "octocat".to_string(),
+            id: 583231,
+            age: Some(42),
+            created_at: None,
+            starred_at: None,
+        }
+    }
+
+    fn write_sample(format: OutputFormat) -> String {
+        let mut output = vec![];
+        let mut sink = format.sink(&mut output, b',');
+        sink.write_record(&sample_record()).unwrap();
+        sink.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn csv_sink_writes_present_columns() {
+        assert_eq!(write_sample(OutputFormat::Csv), "octocat,583231,42\n");
+    }
+
+    #[test]
+    fn csv_sink_uses_delimiter_and_rfc3339_timestamps() {
+        let record = OutputRecord {
+            age: None,
+            starred_at: Some(Utc.with_ymd_and_hms(2021, 3, 24, 12, 0, 0).unwrap()),
+            ..sample_record()
+        };
+        let mut output = vec![];
+        let mut sink = OutputFormat::Csv.sink(&mut output, b';');
+        sink.write_record(&record).unwrap();
+        sink.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "octocat;583231;2021-03-24T12:00:00+00:00\n"
+        );
+    }
+
+    #[test]
+    fn csv_sink_leaves_unknown_creation_dates_empty() {
+        let record = OutputRecord {
+            age: Some(-1),
+            created_at: Some(None),
+            ..sample_record()
+        };
+        let mut output = vec![];
+        let mut sink = OutputFormat::Csv.sink(&mut output, b',');
+        sink.write_record(&record).unwrap();
+        sink.finish().unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "octocat,583231,-1,\n");
+    }
+
+    #[test]
+    fn json_sink_writes_an_array() {
+        let json: serde_json::Value =
+            serde_json::from_str(&write_sample(OutputFormat::Json)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([{"login": "octocat", "id": 583231, "age": 42}])
+        );
+    }
+
+    #[test]
+    fn json_lines_sink_writes_one_object_per_line() {
+        assert_eq!(
+            write_sample(OutputFormat::Jsonl),
+            "{\"login\":\"octocat\",\"id\":583231,\"age\":42}\n"
+        );
+    }
+
+    #[test]
+    fn markdown_sink_writes_a_table() {
+        assert_eq!(
+            write_sample(OutputFormat::Markdown),
+            "| login | id | age |\n| --- | --- | --- |\n| octocat | 583231 | 42 |\n"
+        );
+    }
+}
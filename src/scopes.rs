@@ -0,0 +1,90 @@
+use super::{error, get_for_status, Error};
+use octocrab::Octocrab;
+
+/// Scopes needed for some of the operations this crate supports, with a short description of each
+pub const KNOWN_REQUIREMENTS: [(&str, &str); 3] = [
+    ("user", "blocking accounts for the authenticated user"),
+    ("admin:org", "blocking accounts for an organization"),
+    ("user:follow", "following or unfollowing accounts"),
+];
+
+/// Broader scopes that imply a narrower one (e.g. `user` includes `user:follow`)
+const IMPLIED_BY: [(&str, &str); 6] = [
+    ("user:follow", "user"),
+    ("user:email", "user"),
+    ("read:user", "user"),
+    ("write:org", "admin:org"),
+    ("read:org", "admin:org"),
+    ("read:org", "write:org"),
+];
+
+/// Load the scopes granted to the current token
+///
+/// Returns `None` if GitHub doesn't report scopes (as for fine-grained tokens or GitHub Apps).
+pub async fn get_token_scopes(instance: &Octocrab) -> octocrab::Result<Option<Vec<String>>> {
+    let (status_and_headers, result) = get_for_status(instance, "user").await?;
+    result?;
+
+    Ok(status_and_headers.oauth_scopes())
+}
+
+/// Check whether a set of granted scopes includes the given scope (directly or via a broader one)
+pub fn has_scope<S: AsRef<str>>(granted: &[S], scope: &str) -> bool {
+    granted.iter().any(|value| value.as_ref() == scope)
+        || IMPLIED_BY
+            .iter()
+            .any(|(narrow, broad)| *narrow == scope && has_scope(granted, broad))
+}
+
+/// Fail with an actionable error if the given scope hasn't been granted
+pub fn require_scope(granted: &[String], scope: &str) -> error::Result<()> {
+    if has_scope(granted, scope) {
+        Ok(())
+    } else {
+        Err(Error::MissingScope {
+            scope: scope.to_string(),
+            granted: granted.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_scope_accepts_directly_granted_scope() {
+        assert!(has_scope(&["repo", "user"], "user"));
+    }
+
+    #[test]
+    fn has_scope_accepts_implied_scopes() {
+        assert!(has_scope(&["user"], "user:follow"));
+        assert!(has_scope(&["write:org"], "read:org"));
+        // admin:org implies write:org, which implies read:org
+        assert!(has_scope(&["admin:org"], "read:org"));
+    }
+
+    #[test]
+    fn has_scope_rejects_missing_and_narrower_scopes() {
+        assert!(!has_scope::<&str>(&[], "user"));
+        assert!(!has_scope(&["repo"], "admin:org"));
+        assert!(!has_scope(&["read:org"], "write:org"));
+        assert!(!has_scope(&["user:follow"], "user"));
+    }
+
+    #[test]
+    fn require_scope_reports_missing_scope_and_granted_scopes() {
+        let granted = vec!["repo".to_string(), "read:org".to_string()];
+
+        assert!(require_scope(&granted, "read:org").is_ok());
+
+        match require_scope(&granted, "admin:org") {
+            Err(Error::MissingScope { scope, granted }) => {
+                assert_eq!(scope, "admin:org");
+                assert_eq!(granted, vec!["repo", "read:org"]);
+            }
+            other => panic!("Expected a missing scope error, got {:?}", other),
+        }
+    }
+}